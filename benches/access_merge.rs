@@ -0,0 +1,35 @@
+//! Benchmarks `Access<AccountInfo>::merge_account`'s digest fast path on
+//! the case that motivated it: many sub-logs merging in the same popular
+//! account's `Read`. Only `merge_account` itself is measured; no naive
+//! full-field comparison is benchmarked alongside it.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use revm::primitives::{AccountInfo, Bytecode, U256};
+
+use amethyst::log::Access;
+
+fn popular_account() -> AccountInfo {
+    AccountInfo {
+        balance: U256::from(1_000_000_000_000u64),
+        nonce: 7,
+        code: Some(Bytecode::new_raw([0x60, 0x00].repeat(512).into())),
+        ..Default::default()
+    }
+}
+
+fn bench_merge_account(c: &mut Criterion) {
+    let info = popular_account();
+
+    c.bench_function("merge_account_read_read_matching", |b| {
+        b.iter(|| {
+            let merged =
+                Access::Read(Some(info.clone())).merge_account(Access::Read(Some(info.clone())));
+            black_box(merged)
+        })
+    });
+}
+
+criterion_group!(benches, bench_merge_account);
+criterion_main!(benches);
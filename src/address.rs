@@ -0,0 +1,121 @@
+//! Account addressing.
+
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// A 20-byte EVM account address.
+pub type EvmAddress = primitive_types::H160;
+
+/// Converts to the `Address` type revm's EVM and `Database` trait expect.
+pub fn to_revm(address: EvmAddress) -> revm::primitives::Address {
+    revm::primitives::Address::from(address.to_fixed_bytes())
+}
+
+/// Converts back from revm's `Address` type.
+pub fn from_revm(address: revm::primitives::Address) -> EvmAddress {
+    EvmAddress::from(address.0 .0)
+}
+
+/// Errors raised while parsing a checksummed address.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddrError {
+    /// The string wasn't 40 hex characters (ignoring an optional `0x`).
+    #[error("invalid address length")]
+    InvalidLength,
+    /// The string contained non-hex characters.
+    #[error("invalid hex digit")]
+    InvalidHex,
+    /// The string's letter casing didn't match the EIP-55 checksum.
+    #[error("checksum mismatch")]
+    ChecksumMismatch,
+}
+
+/// Encodes `addr` using the [EIP-55](https://eips.ethereum.org/EIPS/eip-55)
+/// mixed-case checksum, for human-readable debugging in error messages and
+/// logs.
+pub fn to_checksum(addr: &EvmAddress) -> String {
+    let hex = hex::encode(addr.as_bytes());
+    let hash = Keccak256::digest(hex.as_bytes());
+
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, c) in hex.chars().enumerate() {
+        if c.is_ascii_digit() {
+            out.push(c);
+            continue;
+        }
+        // Each hex nibble of the hash decides the casing of the matching
+        // character of the address, taken 4 bits (one nibble) at a time.
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if nibble >= 8 {
+            out.push(c.to_ascii_uppercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Decodes an EIP-55 checksummed address, rejecting a string whose letter
+/// casing doesn't match the checksum.
+pub fn from_checksum(s: &str) -> Result<EvmAddress, AddrError> {
+    let hex_part = s.strip_prefix("0x").unwrap_or(s);
+    if hex_part.len() != 40 {
+        return Err(AddrError::InvalidLength);
+    }
+
+    let mut bytes = [0u8; 20];
+    hex::decode_to_slice(hex_part, &mut bytes).map_err(|_| AddrError::InvalidHex)?;
+    let addr = EvmAddress::from(bytes);
+
+    if to_checksum(&addr)[2..] != *hex_part {
+        return Err(AddrError::ChecksumMismatch);
+    }
+    Ok(addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Known-good EIP-55 test vectors from the spec.
+    const VECTORS: &[&str] = &[
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn round_trips_known_checksummed_addresses() {
+        for vector in VECTORS {
+            let addr = from_checksum(vector).unwrap();
+            assert_eq!(&to_checksum(&addr), vector);
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_casing() {
+        assert_eq!(
+            from_checksum("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"),
+            Err(AddrError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        assert_eq!(from_checksum("0xabcd"), Err(AddrError::InvalidLength));
+    }
+
+    #[test]
+    fn rejects_non_hex() {
+        assert_eq!(
+            from_checksum("0xZZaeb6053F3E94C9b9A09f33669435E7Ef1BeAed"),
+            Err(AddrError::InvalidHex)
+        );
+    }
+}
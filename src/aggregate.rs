@@ -0,0 +1,298 @@
+//! Aggregating many leaf sub-proofs' claims into one computation.
+//!
+//! This crate has no zkVM backend wired in — no `risc0` dependency, and no
+//! code anywhere that holds or cryptographically verifies an actual STARK
+//! receipt. [`ComputationTree::verify`] can therefore only check the
+//! structural half of batch verification: that each leaf's claimed
+//! post-state root chains into the next leaf's claimed pre-state root, the
+//! same linking a real recursive verifier would check before it ever got to
+//! the cryptographic half. Wiring an actual `risc0_zkvm::Receipt` batch
+//! verification call in is host-level work this crate doesn't own yet.
+
+use primitive_types::H256;
+use thiserror::Error;
+
+use crate::journal::{self, ChainError, JournalClaim};
+
+/// One leaf of a [`ComputationTree`]: the claim a sub-proof committed to
+/// its journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeafClaim {
+    pub claim: JournalClaim,
+}
+
+/// Errors [`ComputationTree::verify`] can report against a single leaf.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum VerificationError {
+    /// This leaf's pre-state root doesn't match the previous leaf's
+    /// post-state root, so the two sub-proofs don't compose.
+    #[error("leaf's pre-state root does not chain from the previous leaf's post-state root")]
+    BrokenChain,
+    /// The tree holds more leaves than the configured max depth allows —
+    /// see [`ComputationTree::verify_with_max_depth`].
+    #[error("computation tree has {actual} leaves, exceeding the maximum of {max_depth}")]
+    TooDeep { actual: usize, max_depth: usize },
+}
+
+/// The max depth [`ComputationTree::verify`] enforces. Large enough for any
+/// proof this crate realistically aggregates, small enough that an
+/// adversarial tree claiming more than this is rejected outright rather
+/// than spending unbounded work walking it.
+pub const DEFAULT_MAX_DEPTH: usize = 1_024;
+
+/// A sequence of leaf claims to be verified together, in the order the
+/// sub-proofs they came from were applied.
+#[derive(Debug, Clone, Default)]
+pub struct ComputationTree {
+    leaves: Vec<LeafClaim>,
+}
+
+impl ComputationTree {
+    pub fn new(leaves: Vec<LeafClaim>) -> Self {
+        Self { leaves }
+    }
+
+    /// Like [`ComputationTree::verify_with_max_depth`], capped at
+    /// [`DEFAULT_MAX_DEPTH`].
+    pub fn verify(&self) -> Result<(), (usize, VerificationError)> {
+        self.verify_with_max_depth(DEFAULT_MAX_DEPTH)
+    }
+
+    /// Checks that every leaf's claim chains into the next, failing fast at
+    /// the first broken link and reporting its index — so a caller
+    /// aggregating many leaves doesn't have to re-check each one
+    /// individually to find which sub-proof was invalid.
+    ///
+    /// Rejects outright, before looking at a single claim, a tree holding
+    /// more than `max_depth` leaves — `VerificationError::TooDeep` rather
+    /// than letting a caller walk an adversarially large tree unbounded.
+    /// A malicious prover can claim any number of leaves; callers that
+    /// don't expect a deep aggregation should pass a `max_depth` no larger
+    /// than what their own use case actually needs.
+    pub fn verify_with_max_depth(
+        &self,
+        max_depth: usize,
+    ) -> Result<(), (usize, VerificationError)> {
+        if self.leaves.len() > max_depth {
+            return Err((
+                self.leaves.len(),
+                VerificationError::TooDeep {
+                    actual: self.leaves.len(),
+                    max_depth,
+                },
+            ));
+        }
+        for i in 1..self.leaves.len() {
+            if self.leaves[i - 1].claim.post != self.leaves[i].claim.prev {
+                return Err((i, VerificationError::BrokenChain));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One block's own claim to be composed into a [`BlockTree`] — the claim its
+/// (possibly itself a [`ComputationTree`]-aggregated) proof committed to,
+/// the same way a [`LeafClaim`] is one sub-proof's claim within a single
+/// block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockClaim {
+    pub claim: JournalClaim,
+}
+
+/// The claim a [`BlockTree`]'s aggregated proof would itself commit to: the
+/// first block's pre-state root and the last block's post-state root,
+/// spanning every block the tree verified rather than just one — the
+/// multi-block analog of a single [`JournalClaim`]'s own `prev`/`post`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockTreeRoot {
+    pub first_prev: H256,
+    pub last_post: H256,
+}
+
+/// Errors [`BlockTree::verify`] can report.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlockTreeError {
+    /// Two consecutive blocks didn't chain into one another; see
+    /// [`ChainError`] for which check failed and at what index.
+    #[error(transparent)]
+    Chain(#[from] ChainError),
+    /// A `BlockTree` with no blocks has no `first_prev`/`last_post` to
+    /// report.
+    #[error("a block tree with no blocks has no root claim")]
+    Empty,
+}
+
+/// A sequence of whole blocks' own claims to be verified together and
+/// aggregated into a single recursive proof covering all of them —
+/// analogous to [`ComputationTree`], but chaining blocks rather than the
+/// sub-proof leaves within one block. Verification reuses
+/// [`journal::verify_chain`]'s checks: each block's state picks up exactly
+/// where the previous one left off, each names the previous one as its
+/// parent, block numbers increase by exactly one, and timestamps don't go
+/// backwards.
+#[derive(Debug, Clone, Default)]
+pub struct BlockTree {
+    blocks: Vec<BlockClaim>,
+}
+
+impl BlockTree {
+    pub fn new(blocks: Vec<BlockClaim>) -> Self {
+        Self { blocks }
+    }
+
+    /// Checks that every block in this tree chains into the next, and if so,
+    /// reports the root claim — `(first block's prev, last block's post)` —
+    /// the whole tree's aggregated proof would commit to.
+    ///
+    /// `allow_equal_timestamps` is forwarded to [`journal::verify_chain`]
+    /// unchanged.
+    pub fn verify(&self, allow_equal_timestamps: bool) -> Result<BlockTreeRoot, BlockTreeError> {
+        let claims: Vec<JournalClaim> = self.blocks.iter().map(|block| block.claim).collect();
+        let first = claims.first().ok_or(BlockTreeError::Empty)?;
+        let last = claims.last().expect("non-empty, since `first` succeeded");
+
+        journal::verify_chain(&claims, allow_equal_timestamps)?;
+
+        Ok(BlockTreeRoot {
+            first_prev: first.prev,
+            last_post: last.post,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(prev: u8, post: u8) -> JournalClaim {
+        JournalClaim {
+            prev: H256::repeat_byte(prev),
+            post: H256::repeat_byte(post),
+            bundle_commitment: H256::repeat_byte(0xFF),
+            block_number: 0,
+            parent_block_commitment: H256::zero(),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn verify_accepts_a_chain_of_claims_that_all_link_up() {
+        let tree = ComputationTree::new(vec![
+            LeafClaim {
+                claim: claim(0x00, 0x01),
+            },
+            LeafClaim {
+                claim: claim(0x01, 0x02),
+            },
+            LeafClaim {
+                claim: claim(0x02, 0x03),
+            },
+        ]);
+
+        assert_eq!(tree.verify(), Ok(()));
+    }
+
+    #[test]
+    fn verify_reports_the_index_of_the_first_broken_link() {
+        let tree = ComputationTree::new(vec![
+            LeafClaim {
+                claim: claim(0x00, 0x01),
+            },
+            LeafClaim {
+                claim: claim(0x01, 0x02),
+            },
+            // This leaf's `prev` doesn't match the previous leaf's `post`.
+            LeafClaim {
+                claim: claim(0x99, 0x03),
+            },
+            LeafClaim {
+                claim: claim(0x03, 0x04),
+            },
+        ]);
+
+        assert_eq!(tree.verify(), Err((2, VerificationError::BrokenChain)));
+    }
+
+    #[test]
+    fn verify_with_max_depth_rejects_a_tree_with_more_leaves_than_allowed() {
+        let leaves: Vec<_> = (0u8..5)
+            .map(|i| LeafClaim {
+                claim: claim(i, i + 1),
+            })
+            .collect();
+        let tree = ComputationTree::new(leaves);
+
+        assert_eq!(
+            tree.verify_with_max_depth(3),
+            Err((
+                5,
+                VerificationError::TooDeep {
+                    actual: 5,
+                    max_depth: 3
+                }
+            ))
+        );
+    }
+
+    fn chained_block_claim(
+        state: u8,
+        number: u64,
+        parent_block_commitment: H256,
+        timestamp: u64,
+    ) -> JournalClaim {
+        JournalClaim {
+            prev: H256::repeat_byte(state),
+            post: H256::repeat_byte(state + 1),
+            bundle_commitment: H256::repeat_byte(0xFF),
+            block_number: number,
+            parent_block_commitment,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn verify_aggregates_two_linked_blocks_into_one_root_claim() {
+        let first = chained_block_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_block_claim(0x01, 1, first.commitment(), 101);
+
+        let tree = BlockTree::new(vec![
+            BlockClaim { claim: first },
+            BlockClaim { claim: second },
+        ]);
+
+        assert_eq!(
+            tree.verify(false),
+            Ok(BlockTreeRoot {
+                first_prev: first.prev,
+                last_post: second.post,
+            })
+        );
+    }
+
+    #[test]
+    fn verify_rejects_two_blocks_whose_commitments_do_not_link() {
+        let first = chained_block_claim(0x00, 0, H256::zero(), 100);
+        // Names a parent commitment that isn't `first`'s.
+        let second = chained_block_claim(0x01, 1, H256::repeat_byte(0xDE), 101);
+
+        let tree = BlockTree::new(vec![
+            BlockClaim { claim: first },
+            BlockClaim { claim: second },
+        ]);
+
+        assert_eq!(
+            tree.verify(false),
+            Err(BlockTreeError::Chain(ChainError::BrokenParentCommitment {
+                index: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn verify_rejects_an_empty_block_tree() {
+        let tree = BlockTree::new(vec![]);
+
+        assert_eq!(tree.verify(false), Err(BlockTreeError::Empty));
+    }
+}
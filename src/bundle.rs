@@ -0,0 +1,912 @@
+//! Decoding a sequence of transactions submitted together as one bundle.
+//!
+//! On the wire, a bundle is a sequence of length-prefixed, bincode-encoded
+//! [`EvmTransaction`]s. For very large bundles, decoding every transaction
+//! into a `Vec` up front uses a lot of guest memory; [`decode_bundle_iter`]
+//! yields transactions lazily so callers like `apply_transactions` can
+//! process and drop them one at a time.
+//!
+//! [`prevalidate_bundle`] runs ahead of decoding to reject oversized bundles
+//! cheaply, before spending any time on deserialization or balance checks.
+//! It also checks that the bundle's [`sign_bundle`] signature matches its
+//! claimed sequencer, so a griefer can't attribute a bundle to a sequencer
+//! who never submitted it and drain their balance.
+//!
+//! [`Bundle`] is the typed, decoded form: a sequencer address and its
+//! signature over the transactions, alongside the raw bytes they were
+//! decoded from.
+//!
+//! Nothing about a `(Address, bytes)` pair on its own proves it was read
+//! back from DA in the order it was posted — a malicious or buggy DA client
+//! could reorder or drop frames between calls. [`DaFeed::next_bundle`]
+//! attaches each frame's position to it, and [`apply_block`] rejects any
+//! sequence whose positions aren't strictly increasing.
+
+use std::collections::VecDeque;
+
+use primitive_types::{H256, U256};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+use crate::address::EvmAddress;
+use crate::config::RollupConfig;
+use crate::trie::MerkleTree;
+use crate::tx::EvmTransaction;
+
+const LEN_PREFIX_BYTES: usize = 4;
+const SEQUENCER_BYTES: usize = 20;
+const SIGNATURE_BYTES: usize = 32;
+
+/// Tunable economic parameters for bundle submission, so an operator can
+/// adjust them without a code change: how much a sequencer must pay per
+/// byte of bundle data, and how large a bond a sequencer must hold to be
+/// eligible to submit at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollupEconomics {
+    pub price_per_byte: U256,
+    pub min_bond: U256,
+}
+
+/// Errors raised while decoding a bundle.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DeserializationError {
+    /// The bundle ended partway through a length prefix or a transaction.
+    #[error("truncated bundle: expected {expected} more bytes, found {found}")]
+    Truncated { expected: usize, found: usize },
+    /// A transaction's bytes didn't decode as a valid [`EvmTransaction`].
+    #[error("malformed transaction: {0}")]
+    Malformed(String),
+    /// Decoding would require more than `max` transactions from this
+    /// bundle. Unlike [`BundlePrevalidationError::TooManyTransactions`],
+    /// this doesn't report an exact total: [`decode_bundle_iter`] stops as
+    /// soon as the limit would be exceeded, rather than scanning the rest
+    /// of the bundle just to count it.
+    #[error("bundle requires decoding more than {max} transactions")]
+    TooManyTransactions { max: usize },
+}
+
+/// Errors raised while prevalidating a bundle's raw bytes, before decoding.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BundlePrevalidationError {
+    /// The bundle's encoded size exceeds the configured limit.
+    #[error("bundle of {found} bytes exceeds the {max} byte limit")]
+    BundleTooLarge { found: usize, max: usize },
+    /// The bundle contains more transactions than `max_txs_per_bundle`
+    /// allows.
+    #[error("bundle contains {found} transactions, exceeding the {max} per-bundle limit")]
+    TooManyTransactions { found: usize, max: usize },
+    /// The fee paid for the bundle doesn't cover its byte-priced cost under
+    /// the configured [`RollupEconomics`].
+    #[error("bundle paid {paid} but its {bytes} bytes cost {required} at the configured price")]
+    InsufficientFee {
+        paid: U256,
+        required: U256,
+        bytes: usize,
+    },
+    /// The bundle's length-prefix framing was malformed, so its transaction
+    /// count couldn't even be determined.
+    #[error(transparent)]
+    Framing(#[from] DeserializationError),
+    /// `bytes`' signature doesn't match the one its claimed sequencer would
+    /// have produced — either forged outright, or genuinely signed by a
+    /// different sequencer and misattributed.
+    #[error("bundle signature does not match its claimed sequencer")]
+    BadSequencerSignature,
+}
+
+/// Counts `bytes`' length-prefixed transaction frames without decoding any
+/// of their bodies — cheap enough for [`prevalidate_bundle`] to call before
+/// spending any time on a full [`bincode`] decode.
+fn count_bundle_frames(bytes: &[u8]) -> Result<usize, DeserializationError> {
+    let mut offset = 0;
+    let mut count = 0;
+
+    while offset < bytes.len() {
+        let remaining = bytes.len() - offset;
+        if remaining < LEN_PREFIX_BYTES {
+            return Err(DeserializationError::Truncated {
+                expected: LEN_PREFIX_BYTES,
+                found: remaining,
+            });
+        }
+        let len_bytes: [u8; LEN_PREFIX_BYTES] =
+            bytes[offset..offset + LEN_PREFIX_BYTES].try_into().unwrap();
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        offset += LEN_PREFIX_BYTES;
+
+        let remaining = bytes.len() - offset;
+        if remaining < len {
+            return Err(DeserializationError::Truncated {
+                expected: len,
+                found: remaining,
+            });
+        }
+        offset += len;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Computes the authentication tag a sequencer holding `sequencer_key`
+/// would produce over `bytes`.
+///
+/// This crate has no ECDSA signature-recovery pipeline yet (see
+/// [`crate::signature`]), and no secp256k1 dependency to build one from.
+/// `sign_bundle` stands in with a symmetric Keccak256 MAC keyed on the
+/// sequencer's own registered key instead — enough to bind a bundle to one
+/// specific sequencer and catch a forged attribution, even though it isn't
+/// the asymmetric, publicly-verifiable signature a real sequencer's
+/// private key would produce.
+pub fn sign_bundle(sequencer_key: H256, bytes: &[u8]) -> H256 {
+    let mut preimage = sequencer_key.as_bytes().to_vec();
+    preimage.extend_from_slice(bytes);
+    H256::from(Keccak256::digest(&preimage).as_ref())
+}
+
+/// Rejects `bytes` if `signature` doesn't match [`sign_bundle`]'s output for
+/// `sequencer_key`, if it exceeds `config.max_bundle_bytes`, holds more than
+/// `config.max_txs_per_bundle` transactions, or if `paid` doesn't cover its
+/// cost under `config.economics.price_per_byte`, before any decoding or
+/// balance checks are attempted. A bundle of exactly `config.max_bundle_bytes`
+/// or `config.max_txs_per_bundle`, or a fee exactly covering its cost, is
+/// accepted.
+///
+/// Cheap, DA-spam-bounding gate run ahead of [`deserialize_bundle`]: a
+/// bundle this cheap to reject shouldn't cost a full decode first. Bounding
+/// the transaction count matters independently of the byte limit, since
+/// merging a bundle's log into a block's is `O(n log n)` in its transaction
+/// count. The signature check runs first of all, so a forged bundle never
+/// gets far enough to have its fee charged against the claimed sequencer's
+/// balance.
+pub fn prevalidate_bundle(
+    bytes: &[u8],
+    config: &RollupConfig,
+    paid: U256,
+    sequencer_key: H256,
+    signature: H256,
+) -> Result<(), BundlePrevalidationError> {
+    if sign_bundle(sequencer_key, bytes) != signature {
+        return Err(BundlePrevalidationError::BadSequencerSignature);
+    }
+
+    if bytes.len() > config.max_bundle_bytes {
+        return Err(BundlePrevalidationError::BundleTooLarge {
+            found: bytes.len(),
+            max: config.max_bundle_bytes,
+        });
+    }
+
+    let tx_count = count_bundle_frames(bytes)?;
+    if tx_count > config.max_txs_per_bundle {
+        return Err(BundlePrevalidationError::TooManyTransactions {
+            found: tx_count,
+            max: config.max_txs_per_bundle,
+        });
+    }
+
+    let required = config.economics.price_per_byte * U256::from(bytes.len() as u64);
+    if paid < required {
+        return Err(BundlePrevalidationError::InsufficientFee {
+            paid,
+            required,
+            bytes: bytes.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Encodes `txs` into the bundle wire format.
+pub fn serialize_bundle(txs: &[EvmTransaction]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for tx in txs {
+        let encoded = bincode::serialize(tx).expect("EvmTransaction is always serializable");
+        out.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+        out.extend_from_slice(&encoded);
+    }
+    out
+}
+
+/// A commitment to `txs` standing in for the transactions root Ethereum
+/// blocks commit to. This crate has no Merkle-Patricia trie implementation
+/// yet (see [`crate::trie`]), so this builds [`MerkleTree`]'s binary tree
+/// instead, over each transaction's Keccak256 digest, in the order given —
+/// close enough to bind a bundle to its exact transaction contents, even
+/// though it isn't byte-for-byte the RLP-keyed MPT a canonical Ethereum
+/// client would build.
+///
+/// Panics if `txs` is empty, same as [`MerkleTree::build`].
+pub fn transactions_root(txs: &[EvmTransaction]) -> H256 {
+    let leaves: Vec<H256> = txs
+        .iter()
+        .map(|tx| {
+            let encoded = bincode::serialize(tx).expect("EvmTransaction is always serializable");
+            H256::from(Keccak256::digest(&encoded).as_ref())
+        })
+        .collect();
+    MerkleTree::commit(&leaves)
+}
+
+/// Decodes every transaction in `bytes` into a `Vec` up front, rejecting the
+/// bundle if it holds more than `config.max_txs_per_bundle` transactions.
+pub fn deserialize_bundle(
+    bytes: &[u8],
+    config: &RollupConfig,
+) -> Result<Vec<EvmTransaction>, DeserializationError> {
+    decode_bundle_iter(bytes, config.max_txs_per_bundle).collect()
+}
+
+/// Decodes `bytes` lazily, yielding one transaction at a time instead of
+/// allocating a `Vec` for the whole bundle. Stops (returning `None`) after
+/// the first decoding error, including once decoding the next transaction
+/// would exceed `max_txs_per_bundle`.
+pub fn decode_bundle_iter(
+    bytes: &[u8],
+    max_txs_per_bundle: usize,
+) -> impl Iterator<Item = Result<EvmTransaction, DeserializationError>> + '_ {
+    let mut offset = 0;
+    let mut errored = false;
+    let mut count = 0;
+
+    std::iter::from_fn(move || {
+        if errored || offset == bytes.len() {
+            return None;
+        }
+
+        if count == max_txs_per_bundle {
+            errored = true;
+            return Some(Err(DeserializationError::TooManyTransactions {
+                max: max_txs_per_bundle,
+            }));
+        }
+
+        let remaining = bytes.len() - offset;
+        if remaining < LEN_PREFIX_BYTES {
+            errored = true;
+            return Some(Err(DeserializationError::Truncated {
+                expected: LEN_PREFIX_BYTES,
+                found: remaining,
+            }));
+        }
+        let len_bytes: [u8; LEN_PREFIX_BYTES] =
+            bytes[offset..offset + LEN_PREFIX_BYTES].try_into().unwrap();
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        offset += LEN_PREFIX_BYTES;
+
+        let remaining = bytes.len() - offset;
+        if remaining < len {
+            errored = true;
+            return Some(Err(DeserializationError::Truncated {
+                expected: len,
+                found: remaining,
+            }));
+        }
+        let encoded = &bytes[offset..offset + len];
+        offset += len;
+        count += 1;
+
+        match bincode::deserialize(encoded) {
+            Ok(tx) => Some(Ok(tx)),
+            Err(e) => {
+                errored = true;
+                Some(Err(DeserializationError::Malformed(e.to_string())))
+            }
+        }
+    })
+}
+
+/// A bundle of transactions submitted together by one sequencer.
+///
+/// On the wire, a bundle is the sequencer's 20-byte address, its 32-byte
+/// [`sign_bundle`] signature over the transactions that follow, and then
+/// the transactions themselves in [`serialize_bundle`]'s format. `Bundle`
+/// keeps the raw encoded bytes alongside the decoded fields, so a
+/// commitment to the bundle (e.g. for DA) doesn't require re-encoding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    sequencer: EvmAddress,
+    signature: H256,
+    transactions: Vec<EvmTransaction>,
+    raw: Vec<u8>,
+}
+
+impl Bundle {
+    /// Builds a bundle from its decoded fields, signing it with
+    /// `sequencer_key` and encoding the wire bytes.
+    pub fn new(
+        sequencer: EvmAddress,
+        transactions: Vec<EvmTransaction>,
+        sequencer_key: H256,
+    ) -> Bundle {
+        let encoded_txs = serialize_bundle(&transactions);
+        let signature = sign_bundle(sequencer_key, &encoded_txs);
+
+        let mut raw = sequencer.as_bytes().to_vec();
+        raw.extend_from_slice(signature.as_bytes());
+        raw.extend_from_slice(&encoded_txs);
+        Bundle {
+            sequencer,
+            signature,
+            transactions,
+            raw,
+        }
+    }
+
+    /// Decodes a bundle from its wire format: a 20-byte sequencer address,
+    /// a 32-byte signature, and then its transactions. Does not itself
+    /// check that the signature was produced by the claimed sequencer's
+    /// key — a caller wanting that should verify it via
+    /// [`prevalidate_bundle`] before (or instead of) decoding.
+    pub fn decode(bytes: &[u8]) -> Result<Bundle, DeserializationError> {
+        let header_bytes = SEQUENCER_BYTES + SIGNATURE_BYTES;
+        if bytes.len() < header_bytes {
+            return Err(DeserializationError::Truncated {
+                expected: header_bytes,
+                found: bytes.len(),
+            });
+        }
+        let sequencer = EvmAddress::from_slice(&bytes[..SEQUENCER_BYTES]);
+        let signature = H256::from_slice(&bytes[SEQUENCER_BYTES..header_bytes]);
+        // `Bundle::decode` has no transaction-count limit of its own; a
+        // caller wanting one should enforce it via `prevalidate_bundle`
+        // before decoding.
+        let unlimited = RollupConfig {
+            max_txs_per_bundle: usize::MAX,
+            ..RollupConfig::default()
+        };
+        let transactions = deserialize_bundle(&bytes[header_bytes..], &unlimited)?;
+        Ok(Bundle {
+            sequencer,
+            signature,
+            transactions,
+            raw: bytes.to_vec(),
+        })
+    }
+
+    /// The sequencer that submitted this bundle.
+    pub fn sequencer(&self) -> EvmAddress {
+        self.sequencer
+    }
+
+    /// This bundle's signature over its transactions, claimed to have been
+    /// produced by [`Bundle::sequencer`]'s key.
+    pub fn signature(&self) -> H256 {
+        self.signature
+    }
+
+    /// This bundle's transactions, in submission order.
+    pub fn transactions(&self) -> &[EvmTransaction] {
+        &self.transactions
+    }
+
+    /// The bundle's raw wire encoding.
+    pub fn raw_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// A commitment to this bundle's exact wire bytes: the Keccak256 hash
+    /// of [`Bundle::raw_bytes`].
+    ///
+    /// Bound into the guest journal alongside the pre/post state roots, so
+    /// a verifier can tie a proof to the specific DA data it processed.
+    pub fn commitment(&self) -> H256 {
+        H256::from(Keccak256::digest(&self.raw).as_ref())
+    }
+}
+
+/// One frame read back from DA: its position in the DA stream, the address
+/// that submitted it, and its still-encoded bundle bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaFrame {
+    pub position: u64,
+    pub submitter: EvmAddress,
+    pub bytes: Vec<u8>,
+}
+
+/// A DA client's frame stream, replayed in the order it was recorded.
+///
+/// This crate has no live DA client of its own yet; `DaFeed` is the minimal
+/// stand-in needed to give [`apply_block`] something to enforce an ordering
+/// contract against, mirroring how [`crate::host::HostDB`] replays a
+/// recorded witness rather than touching live chain state.
+#[derive(Debug, Default)]
+pub struct DaFeed {
+    frames: VecDeque<DaFrame>,
+}
+
+impl DaFeed {
+    /// Builds a feed that will yield `frames` in order.
+    pub fn new(frames: Vec<DaFrame>) -> DaFeed {
+        DaFeed {
+            frames: frames.into(),
+        }
+    }
+
+    /// Returns the next frame in the feed, or `None` once exhausted.
+    pub fn next_bundle(&mut self) -> Option<DaFrame> {
+        self.frames.pop_front()
+    }
+}
+
+/// Errors raised while applying a block's worth of DA frames.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlockApplyError {
+    /// A frame's position wasn't strictly greater than the previous frame's,
+    /// meaning the feed reordered, duplicated, or skipped a frame.
+    #[error(
+        "frame at position {found} is not strictly greater than the previous position {previous}"
+    )]
+    OutOfOrder { previous: u64, found: u64 },
+    /// A frame's bytes didn't decode as a valid [`Bundle`].
+    #[error(transparent)]
+    Deserialization(#[from] DeserializationError),
+}
+
+/// Drains `feed` to completion, decoding each frame into a [`Bundle`] and
+/// asserting that positions strictly increase from one frame to the next.
+/// Returns the decoded bundles in feed order, or the first error
+/// encountered — either a reordered/skipped frame or a malformed one.
+pub fn apply_block(feed: &mut DaFeed) -> Result<Vec<Bundle>, BlockApplyError> {
+    let mut bundles = Vec::new();
+    let mut last_position: Option<u64> = None;
+
+    while let Some(frame) = feed.next_bundle() {
+        if let Some(previous) = last_position {
+            if frame.position <= previous {
+                return Err(BlockApplyError::OutOfOrder {
+                    previous,
+                    found: frame.position,
+                });
+            }
+        }
+        last_position = Some(frame.position);
+        bundles.push(Bundle::decode(&frame.bytes)?);
+    }
+
+    Ok(bundles)
+}
+
+/// A sequencer's bonded balance, tracked so [`SequencerState::is_eligible`]
+/// can gate bundle submission on holding at least a configured
+/// [`RollupEconomics::min_bond`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequencerState {
+    pub bonded_balance: U256,
+}
+
+impl SequencerState {
+    /// Whether this sequencer currently holds enough bond to submit bundles
+    /// under `economics`.
+    pub fn is_eligible(&self, economics: &RollupEconomics) -> bool {
+        self.bonded_balance >= economics.min_bond
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::EvmAddress;
+    use crate::tx::{Eip1559Tx, LegacyTx, TxCommon};
+    use primitive_types::U256 as PU256;
+
+    fn sequencer_key() -> H256 {
+        H256::repeat_byte(0xEE)
+    }
+
+    fn config_with(max_bundle_bytes: usize, max_txs_per_bundle: usize) -> RollupConfig {
+        RollupConfig {
+            max_bundle_bytes,
+            max_txs_per_bundle,
+            economics: free_economics(),
+            ..RollupConfig::default()
+        }
+    }
+
+    fn sample_txs() -> Vec<EvmTransaction> {
+        let common = |nonce| TxCommon {
+            chain_id: 1,
+            nonce,
+            gas_limit: 21_000,
+            to: Some(EvmAddress::repeat_byte(0xCC)),
+            value: PU256::zero(),
+            data: vec![],
+        };
+        vec![
+            EvmTransaction::Legacy(LegacyTx {
+                common: common(0),
+                gas_price: PU256::from(5u64),
+                access_list: vec![],
+            }),
+            EvmTransaction::Eip1559(Eip1559Tx {
+                common: common(1),
+                max_fee_per_gas: PU256::from(10u64),
+                max_priority_fee_per_gas: PU256::from(1u64),
+                access_list: vec![],
+            }),
+        ]
+    }
+
+    #[test]
+    fn iterator_yields_same_sequence_as_eager_decode() {
+        let bytes = serialize_bundle(&sample_txs());
+
+        let eager = deserialize_bundle(&bytes, &config_with(usize::MAX, usize::MAX)).unwrap();
+        let lazy: Result<Vec<_>, _> = decode_bundle_iter(&bytes, usize::MAX).collect();
+
+        assert_eq!(eager, lazy.unwrap());
+        assert_eq!(eager, sample_txs());
+    }
+
+    #[test]
+    fn rejects_truncated_length_prefix() {
+        let mut bytes = serialize_bundle(&sample_txs());
+        bytes.truncate(2);
+        assert!(matches!(
+            deserialize_bundle(&bytes, &config_with(usize::MAX, usize::MAX)),
+            Err(DeserializationError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_transaction_body() {
+        let mut bytes = serialize_bundle(&sample_txs());
+        bytes.truncate(bytes.len() - 1);
+        assert!(matches!(
+            deserialize_bundle(&bytes, &config_with(usize::MAX, usize::MAX)),
+            Err(DeserializationError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn deserialize_bundle_rejects_one_transaction_more_than_the_configured_limit() {
+        let bytes = serialize_bundle(&sample_txs());
+
+        assert_eq!(
+            deserialize_bundle(&bytes, &config_with(usize::MAX, sample_txs().len() - 1)),
+            Err(DeserializationError::TooManyTransactions {
+                max: sample_txs().len() - 1
+            })
+        );
+    }
+
+    fn free_economics() -> RollupEconomics {
+        RollupEconomics {
+            price_per_byte: PU256::zero(),
+            min_bond: PU256::zero(),
+        }
+    }
+
+    #[test]
+    fn prevalidate_accepts_a_bundle_exactly_at_the_limit() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+        assert_eq!(
+            prevalidate_bundle(
+                &bytes,
+                &config_with(bytes.len(), sample_txs().len()),
+                PU256::zero(),
+                key,
+                signature,
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn prevalidate_rejects_a_bundle_one_byte_over_the_limit() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+        assert_eq!(
+            prevalidate_bundle(
+                &bytes,
+                &config_with(bytes.len() - 1, sample_txs().len()),
+                PU256::zero(),
+                key,
+                signature,
+            ),
+            Err(BundlePrevalidationError::BundleTooLarge {
+                found: bytes.len(),
+                max: bytes.len() - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn prevalidate_rejects_one_transaction_more_than_the_configured_limit() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+        assert_eq!(
+            prevalidate_bundle(
+                &bytes,
+                &config_with(bytes.len(), sample_txs().len() - 1),
+                PU256::zero(),
+                key,
+                signature,
+            ),
+            Err(BundlePrevalidationError::TooManyTransactions {
+                found: sample_txs().len(),
+                max: sample_txs().len() - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn prevalidate_accepts_a_fee_exactly_covering_the_configured_price() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+        let economics = RollupEconomics {
+            price_per_byte: PU256::from(2u64),
+            min_bond: PU256::zero(),
+        };
+        let required = economics.price_per_byte * PU256::from(bytes.len() as u64);
+        let config = RollupConfig {
+            max_bundle_bytes: bytes.len(),
+            max_txs_per_bundle: sample_txs().len(),
+            economics,
+            ..RollupConfig::default()
+        };
+
+        assert_eq!(
+            prevalidate_bundle(&bytes, &config, required, key, signature),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn prevalidate_rejects_a_fee_one_below_the_configured_price() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+        let economics = RollupEconomics {
+            price_per_byte: PU256::from(2u64),
+            min_bond: PU256::zero(),
+        };
+        let required = economics.price_per_byte * PU256::from(bytes.len() as u64);
+        let config = RollupConfig {
+            max_bundle_bytes: bytes.len(),
+            max_txs_per_bundle: sample_txs().len(),
+            economics,
+            ..RollupConfig::default()
+        };
+
+        assert_eq!(
+            prevalidate_bundle(
+                &bytes,
+                &config,
+                required - PU256::from(1u64),
+                key,
+                signature,
+            ),
+            Err(BundlePrevalidationError::InsufficientFee {
+                paid: required - PU256::from(1u64),
+                required,
+                bytes: bytes.len(),
+            })
+        );
+    }
+
+    #[test]
+    fn prevalidate_accepts_a_bundle_with_a_valid_sequencer_signature() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+
+        assert_eq!(
+            prevalidate_bundle(
+                &bytes,
+                &config_with(bytes.len(), sample_txs().len()),
+                PU256::zero(),
+                key,
+                signature,
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn prevalidate_rejects_a_forged_sequencer_signature() {
+        let bytes = serialize_bundle(&sample_txs());
+        let key = sequencer_key();
+        // Signed with a different key than the one presented for
+        // verification — a griefer attributing someone else's bundle to
+        // this sequencer, or vice versa.
+        let forged = sign_bundle(H256::repeat_byte(0x99), &bytes);
+
+        assert_eq!(
+            prevalidate_bundle(
+                &bytes,
+                &config_with(bytes.len(), sample_txs().len()),
+                PU256::zero(),
+                key,
+                forged,
+            ),
+            Err(BundlePrevalidationError::BadSequencerSignature)
+        );
+    }
+
+    #[test]
+    fn raising_the_price_per_byte_turns_a_previously_sufficient_fee_insufficient() {
+        let bytes = serialize_bundle(&sample_txs());
+        let paid = PU256::from(bytes.len() as u64);
+        let key = sequencer_key();
+        let signature = sign_bundle(key, &bytes);
+
+        let cheap = RollupConfig {
+            max_bundle_bytes: bytes.len(),
+            max_txs_per_bundle: sample_txs().len(),
+            economics: RollupEconomics {
+                price_per_byte: PU256::from(1u64),
+                min_bond: PU256::zero(),
+            },
+            ..RollupConfig::default()
+        };
+        assert_eq!(
+            prevalidate_bundle(&bytes, &cheap, paid, key, signature),
+            Ok(())
+        );
+
+        let expensive = RollupConfig {
+            economics: RollupEconomics {
+                price_per_byte: PU256::from(1_000u64),
+                min_bond: PU256::zero(),
+            },
+            ..cheap
+        };
+        assert!(prevalidate_bundle(&bytes, &expensive, paid, key, signature).is_err());
+    }
+
+    #[test]
+    fn is_eligible_tracks_the_configured_min_bond() {
+        let sequencer = SequencerState {
+            bonded_balance: PU256::from(50u64),
+        };
+
+        let lenient = RollupEconomics {
+            price_per_byte: PU256::zero(),
+            min_bond: PU256::from(10u64),
+        };
+        assert!(sequencer.is_eligible(&lenient));
+
+        let strict = RollupEconomics {
+            price_per_byte: PU256::zero(),
+            min_bond: PU256::from(100u64),
+        };
+        assert!(!sequencer.is_eligible(&strict));
+    }
+
+    #[test]
+    fn bundle_round_trips_through_its_wire_encoding() {
+        let sequencer = EvmAddress::repeat_byte(0x42);
+        let bundle = Bundle::new(sequencer, sample_txs(), sequencer_key());
+
+        let decoded = Bundle::decode(bundle.raw_bytes()).unwrap();
+
+        assert_eq!(decoded.sequencer(), sequencer);
+        assert_eq!(decoded.signature(), bundle.signature());
+        assert_eq!(
+            decoded.transactions().iter().collect::<Vec<_>>(),
+            sample_txs().iter().collect::<Vec<_>>()
+        );
+        assert_eq!(decoded, bundle);
+    }
+
+    #[test]
+    fn bundles_signed_with_different_keys_carry_different_signatures() {
+        let sequencer = EvmAddress::repeat_byte(0x42);
+        let a = Bundle::new(sequencer, sample_txs(), sequencer_key());
+        let b = Bundle::new(sequencer, sample_txs(), H256::repeat_byte(0x99));
+
+        assert_ne!(a.signature(), b.signature());
+        assert_ne!(a.raw_bytes(), b.raw_bytes());
+    }
+
+    #[test]
+    fn apply_block_accepts_strictly_increasing_positions() {
+        let sequencer = EvmAddress::repeat_byte(0x42);
+        let bundle = Bundle::new(sequencer, sample_txs(), sequencer_key());
+        let mut feed = DaFeed::new(vec![
+            DaFrame {
+                position: 0,
+                submitter: sequencer,
+                bytes: bundle.raw_bytes().to_vec(),
+            },
+            DaFrame {
+                position: 1,
+                submitter: sequencer,
+                bytes: bundle.raw_bytes().to_vec(),
+            },
+        ]);
+
+        let bundles = apply_block(&mut feed).unwrap();
+
+        assert_eq!(bundles, vec![bundle.clone(), bundle]);
+    }
+
+    #[test]
+    fn apply_block_rejects_an_out_of_order_frame() {
+        let sequencer = EvmAddress::repeat_byte(0x42);
+        let bundle = Bundle::new(sequencer, sample_txs(), sequencer_key());
+        let mut feed = DaFeed::new(vec![
+            DaFrame {
+                position: 1,
+                submitter: sequencer,
+                bytes: bundle.raw_bytes().to_vec(),
+            },
+            DaFrame {
+                position: 0,
+                submitter: sequencer,
+                bytes: bundle.raw_bytes().to_vec(),
+            },
+        ]);
+
+        assert_eq!(
+            apply_block(&mut feed),
+            Err(BlockApplyError::OutOfOrder {
+                previous: 1,
+                found: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn apply_block_rejects_a_repeated_position() {
+        let sequencer = EvmAddress::repeat_byte(0x42);
+        let bundle = Bundle::new(sequencer, sample_txs(), sequencer_key());
+        let mut feed = DaFeed::new(vec![
+            DaFrame {
+                position: 0,
+                submitter: sequencer,
+                bytes: bundle.raw_bytes().to_vec(),
+            },
+            DaFrame {
+                position: 0,
+                submitter: sequencer,
+                bytes: bundle.raw_bytes().to_vec(),
+            },
+        ]);
+
+        assert_eq!(
+            apply_block(&mut feed),
+            Err(BlockApplyError::OutOfOrder {
+                previous: 0,
+                found: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn transactions_root_matches_a_merkle_tree_over_the_transactions_own_digests() {
+        let txs = sample_txs();
+
+        let expected_leaves: Vec<H256> = txs
+            .iter()
+            .map(|tx| {
+                let encoded = bincode::serialize(tx).unwrap();
+                H256::from(Keccak256::digest(&encoded).as_ref())
+            })
+            .collect();
+        let expected = MerkleTree::commit(&expected_leaves);
+
+        assert_eq!(transactions_root(&txs), expected);
+    }
+
+    #[test]
+    fn transactions_root_is_sensitive_to_order_and_content() {
+        let txs = sample_txs();
+        let mut reordered = txs.clone();
+        reordered.reverse();
+
+        assert_ne!(transactions_root(&txs), transactions_root(&reordered));
+
+        let mut changed = txs.clone();
+        if let EvmTransaction::Legacy(tx) = &mut changed[0] {
+            tx.common.nonce += 1;
+        }
+        assert_ne!(transactions_root(&txs), transactions_root(&changed));
+    }
+}
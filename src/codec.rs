@@ -0,0 +1,167 @@
+//! Fixed-width big-endian byte (de)serialization for `primitive-types`'
+//! `H160`/`H256`/`U256`, for use via `#[serde(with = "...")]` wherever a
+//! field needs a stable, compact encoding independent of serde defaults.
+//!
+//! `primitive-types`' own `Serialize` impls (behind its `serde` feature)
+//! always encode as a `0x`-prefixed hex string — and for `U256`, with
+//! leading zero bytes stripped, so two values of different magnitude don't
+//! even serialize to the same width. That's fine for human-readable JSON,
+//! but not what a binary-encoded, content-addressed journal or log wants: a
+//! fixed 20 or 32 raw bytes, every time, regardless of the encoded value.
+
+use primitive_types::{H160, H256, U256};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The version every [`versioned`]-wrapped wire encoding in this crate
+/// currently prepends: [`JournalClaim::encode`](crate::journal::JournalClaim::encode),
+/// [`encode_log`](crate::log::encode_log), and [`EvmStateLog::commitment`]
+/// (crate::log::EvmStateLog::commitment). Bump this, and the matching
+/// decoder's `UnsupportedVersion` check, the day any of those formats'
+/// byte layout actually changes — not before, since an unused bump buys
+/// nothing and just desynchronizes encoders and decoders that were fine.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Prepends [`ENCODING_VERSION`] to `body`. The shared first step behind
+/// every versioned encoding in this crate, so a future format change only
+/// has to touch this once rather than at each call site.
+pub fn versioned(mut body: Vec<u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(body.len() + 1);
+    bytes.push(ENCODING_VERSION);
+    bytes.append(&mut body);
+    bytes
+}
+
+/// Splits a [`versioned`]-wrapped buffer's leading version byte off the
+/// rest, checking it against [`ENCODING_VERSION`]. On success, returns the
+/// body that follows it, ready to hand to the inner decoder. On mismatch,
+/// returns the version byte actually found — `0` for an empty buffer,
+/// which is never a valid encoding version — for the caller to wrap in
+/// its own `UnsupportedVersion` error.
+pub fn strip_version(bytes: &[u8]) -> Result<&[u8], u8> {
+    match bytes.split_first() {
+        Some((&ENCODING_VERSION, rest)) => Ok(rest),
+        Some((&other, _)) => Err(other),
+        None => Err(0),
+    }
+}
+
+/// `H160` as its raw 20 bytes.
+pub mod h160 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &H160, serializer: S) -> Result<S::Ok, S::Error> {
+        value.0.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<H160, D::Error> {
+        Ok(H160(<[u8; 20]>::deserialize(deserializer)?))
+    }
+}
+
+/// `H256` as its raw 32 bytes.
+pub mod h256 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &H256, serializer: S) -> Result<S::Ok, S::Error> {
+        value.0.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<H256, D::Error> {
+        Ok(H256(<[u8; 32]>::deserialize(deserializer)?))
+    }
+}
+
+/// `U256` as its 32 big-endian bytes — not its native little-endian limb
+/// representation.
+pub mod u256 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        bytes.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let bytes = <[u8; 32]>::deserialize(deserializer)?;
+        Ok(U256::from_big_endian(&bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versioned_prepends_the_current_encoding_version() {
+        let bytes = versioned(vec![1, 2, 3]);
+        assert_eq!(bytes, vec![ENCODING_VERSION, 1, 2, 3]);
+    }
+
+    #[test]
+    fn strip_version_recovers_the_body_of_a_versioned_buffer() {
+        let bytes = versioned(vec![1, 2, 3]);
+        assert_eq!(strip_version(&bytes), Ok(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn strip_version_reports_a_mismatched_version_byte() {
+        let bytes = vec![ENCODING_VERSION + 1, 1, 2, 3];
+        assert_eq!(strip_version(&bytes), Err(ENCODING_VERSION + 1));
+    }
+
+    #[test]
+    fn strip_version_reports_an_empty_buffer_as_version_zero() {
+        assert_eq!(strip_version(&[]), Err(0));
+    }
+
+    #[test]
+    fn h160_round_trips_through_bincode() {
+        let value = H160::repeat_byte(0x42);
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper(#[serde(with = "h160")] H160);
+
+        let encoded = bincode::serialize(&Wrapper(value)).unwrap();
+        assert_eq!(encoded.len(), 20);
+        let decoded: Wrapper = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.0, value);
+    }
+
+    #[test]
+    fn h256_round_trips_through_bincode() {
+        let value = H256::repeat_byte(0x42);
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper(#[serde(with = "h256")] H256);
+
+        let encoded = bincode::serialize(&Wrapper(value)).unwrap();
+        assert_eq!(encoded.len(), 32);
+        let decoded: Wrapper = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.0, value);
+    }
+
+    #[test]
+    fn u256_encodes_to_exactly_32_bytes_big_endian_and_round_trips() {
+        let value = U256::from(0x1234_5678u64);
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper(#[serde(with = "u256")] U256);
+
+        let encoded = bincode::serialize(&Wrapper(value)).unwrap();
+        assert_eq!(encoded.len(), 32);
+        // Big-endian: the value's low bytes land at the end of the buffer.
+        assert_eq!(&encoded[28..], &[0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(&encoded[..28], &[0u8; 28]);
+
+        let decoded: Wrapper = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.0, value);
+    }
+
+    #[test]
+    fn u256_encoded_width_is_independent_of_magnitude() {
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper(#[serde(with = "u256")] U256);
+
+        let small = bincode::serialize(&Wrapper(U256::from(1u64))).unwrap();
+        let large = bincode::serialize(&Wrapper(U256::MAX)).unwrap();
+        assert_eq!(small.len(), large.len());
+    }
+}
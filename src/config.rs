@@ -0,0 +1,108 @@
+//! A rollup's tunable parameters, consolidated into one [`RollupConfig`]
+//! instead of threaded individually through every function that needs one
+//! of them.
+//!
+//! [`RollupConfig::default`] gives mainnet-like settings: the latest spec,
+//! chain ID 1, EIP-170's contract code size limit, and no toleration of
+//! pre-EIP-155 transactions or zero-gas-price transactions.
+
+use primitive_types::U256;
+use revm::primitives::SpecId;
+
+use crate::bundle::RollupEconomics;
+
+/// EIP-170's contract code size limit (~24KB), revm's own default for
+/// [`revm::primitives::CfgEnv::limit_contract_code_size`].
+const EIP_170_MAX_CODE_SIZE: usize = 0x6000;
+
+/// Every tunable this crate's execution and bundle-handling paths need,
+/// gathered into one value instead of threaded individually through
+/// [`crate::evm::run_standalone`], [`crate::bundle::prevalidate_bundle`], and
+/// [`crate::bundle::deserialize_bundle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollupConfig {
+    /// The chain ID transactions must be signed for. Checked against each
+    /// transaction's own `chain_id` by revm, unless `allow_pre_155` lets a
+    /// transaction through without that check.
+    pub chain_id: u64,
+    pub spec_id: SpecId,
+    pub economics: RollupEconomics,
+    /// The largest encoded bundle [`crate::bundle::prevalidate_bundle`]
+    /// accepts.
+    pub max_bundle_bytes: usize,
+    /// The most transactions a single bundle may decode into.
+    pub max_txs_per_bundle: usize,
+    /// The largest contract code revm will let a `CREATE`/`CREATE2` deploy,
+    /// enforced via [`revm::primitives::CfgEnv::limit_contract_code_size`].
+    pub max_code_size: usize,
+    /// Lets a transaction whose `chain_id` doesn't match this config's
+    /// through anyway, the same way revm already treats a transaction with
+    /// no `chain_id` at all (pre-EIP-155) — no replay protection, but not
+    /// rejected either.
+    pub allow_pre_155: bool,
+    /// Lets [`crate::evm::run_standalone`] (and
+    /// [`crate::evm::run_standalone_traced`]) run a transaction whose
+    /// effective gas price is zero — e.g. a sponsored or meta-transaction
+    /// rollup. Checked against the effective gas price, not the
+    /// transaction's raw `gas_price`/`max_fee_per_gas`, so an EIP-1559
+    /// transaction with `max_fee_per_gas == block.basefee` and no priority
+    /// fee still counts as paying even when this is `false`.
+    pub allow_zero_gas_price: bool,
+    /// Passed through to [`crate::evm::apply_transactions`]'s
+    /// `bundle_atomic` parameter by a caller that wants its on/off setting
+    /// to live in config rather than be threaded through by hand: when
+    /// `true`, a bundle containing even one transaction that fails
+    /// validation is rejected outright, rather than having that one
+    /// transaction quietly excluded and the rest applied.
+    pub bundle_atomic: bool,
+    /// Passed through to [`crate::journal::verify_chain`]'s
+    /// `allow_equal_timestamps` parameter: when `false`, each block's
+    /// timestamp must come strictly after its parent's; when `true`, two
+    /// consecutive blocks may share a timestamp.
+    pub allow_equal_timestamps: bool,
+    /// Passed through to [`crate::evm::apply_transactions`]'s
+    /// `max_log_accesses` parameter: the most state accesses (touched
+    /// accounts plus touched storage slots) a single block's merged log may
+    /// hold before further transactions are excluded and left for the next
+    /// block, bounding proving cost and memory. `usize::MAX` means no cap.
+    pub max_log_accesses: usize,
+}
+
+impl Default for RollupConfig {
+    fn default() -> Self {
+        RollupConfig {
+            chain_id: 1,
+            spec_id: SpecId::LATEST,
+            economics: RollupEconomics {
+                price_per_byte: U256::zero(),
+                min_bond: U256::zero(),
+            },
+            max_bundle_bytes: 128 * 1024,
+            max_txs_per_bundle: 1_000,
+            max_code_size: EIP_170_MAX_CODE_SIZE,
+            allow_pre_155: false,
+            allow_zero_gas_price: false,
+            bundle_atomic: false,
+            allow_equal_timestamps: false,
+            max_log_accesses: usize::MAX,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_mainnet_like() {
+        let config = RollupConfig::default();
+        assert_eq!(config.chain_id, 1);
+        assert_eq!(config.spec_id, SpecId::LATEST);
+        assert_eq!(config.max_code_size, EIP_170_MAX_CODE_SIZE);
+        assert!(!config.allow_pre_155);
+        assert!(!config.allow_zero_gas_price);
+        assert!(!config.bundle_atomic);
+        assert!(!config.allow_equal_timestamps);
+        assert_eq!(config.max_log_accesses, usize::MAX);
+    }
+}
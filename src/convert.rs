@@ -0,0 +1,93 @@
+//! Conversions between `primitive-types`' `H256`/`U256` and revm's own
+//! mirror types.
+//!
+//! The two crates' types are bit-for-bit compatible today, but nothing
+//! guarantees that stays true as revm's version drifts — and some
+//! conversions (a `U256` down to `u64`) are narrowing regardless. Routing
+//! every conversion through here means a future mismatch is one module to
+//! fix, and narrowing conversions fail loudly instead of silently
+//! truncating.
+
+use primitive_types::{H256, U256};
+use thiserror::Error;
+
+/// Errors raised by a narrowing conversion in this module.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The value didn't fit in the narrower target type.
+    #[error("{value} overflows u64")]
+    U256OverflowsU64 { value: U256 },
+}
+
+/// Converts to revm's `U256` representation. Infallible: both types are
+/// 256-bit little-endian limb arrays.
+pub fn u256_to_revm(value: U256) -> revm::primitives::U256 {
+    revm::primitives::U256::from_limbs(value.0)
+}
+
+/// Converts back from revm's `U256` representation. Infallible, for the
+/// same reason as [`u256_to_revm`].
+pub fn u256_from_revm(value: revm::primitives::U256) -> U256 {
+    U256(value.into_limbs())
+}
+
+/// Converts to revm's `B256` representation. Infallible: both types are
+/// 32-byte arrays.
+pub fn h256_to_revm(value: H256) -> revm::primitives::B256 {
+    revm::primitives::B256::from(value.0)
+}
+
+/// Converts back from revm's `B256` representation. Infallible, for the
+/// same reason as [`h256_to_revm`].
+pub fn h256_from_revm(value: revm::primitives::B256) -> H256 {
+    H256(value.0)
+}
+
+/// Narrows `value` to a `u64`, erroring instead of truncating if it doesn't
+/// fit. Block numbers and similar counters are conceptually `u64`-sized even
+/// when carried around as a full `U256`; this is the guard that catches a
+/// value that isn't actually one of those before it gets used as one.
+pub fn u256_to_u64(value: U256) -> Result<u64, ConvertError> {
+    if value > U256::from(u64::MAX) {
+        return Err(ConvertError::U256OverflowsU64 { value });
+    }
+    Ok(value.as_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u256_round_trips_through_the_revm_representation() {
+        let value = U256::from(0x1234_5678_9abc_def0u64);
+        assert_eq!(u256_from_revm(u256_to_revm(value)), value);
+    }
+
+    #[test]
+    fn h256_round_trips_through_the_revm_representation() {
+        let value = H256::repeat_byte(0x42);
+        assert_eq!(h256_from_revm(h256_to_revm(value)), value);
+    }
+
+    #[test]
+    fn u256_to_u64_accepts_a_value_exactly_at_the_boundary() {
+        let value = U256::from(u64::MAX);
+        assert_eq!(u256_to_u64(value), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn u256_to_u64_rejects_a_value_one_past_the_boundary() {
+        let value = U256::from(u64::MAX) + U256::from(1u64);
+        assert_eq!(
+            u256_to_u64(value),
+            Err(ConvertError::U256OverflowsU64 { value })
+        );
+    }
+
+    #[test]
+    fn u256_to_u64_rejects_a_value_that_overflows_far_past_u64() {
+        let value = U256::from(u64::MAX) * U256::from(1_000_000u64);
+        assert!(u256_to_u64(value).is_err());
+    }
+}
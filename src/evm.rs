@@ -0,0 +1,3835 @@
+//! Driving revm over a bundle of transactions.
+
+use std::collections::HashMap;
+
+use primitive_types::{H256, U256 as PU256};
+use revm::primitives::{
+    Account, AccountInfo, Address, BlockEnv, Bytecode, EVMError, Env, ExecutionResult, Halt,
+    HashMap as RevmHashMap, SpecId, B256, U256,
+};
+use revm::{Database, DatabaseCommit, EVM};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+use crate::address::{self, EvmAddress};
+use crate::config::RollupConfig;
+use crate::log::{Access, AccountLogEntry, EvmStateLog};
+use crate::trie::MerkleTree;
+use crate::tx::{EvmTransaction, SetCodeAuthorization};
+
+/// The fraction of a block's gas limit treated as its gas target, per
+/// EIP-1559.
+const ELASTICITY_MULTIPLIER: u64 = 2;
+/// The largest fraction of the base fee that can change between blocks, per
+/// EIP-1559.
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+
+/// Which of a bundle's transactions were actually applied to a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxTree {
+    /// `includes[i]` is `true` iff `txs[i]` was applied to this block.
+    pub includes: Vec<bool>,
+}
+
+/// A deposit crediting an L2 address's balance from an L1 bridge, authorized
+/// by its L1 inclusion rather than a signature. Unlike an [`EvmTransaction`],
+/// it has no gas limit, nonce, or sender to validate — [`apply_transactions`]
+/// applies it directly to account state, crediting a fresh address just as
+/// readily as an existing one.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DepositTransaction {
+    pub target: EvmAddress,
+    pub amount: PU256,
+}
+
+/// A withdrawal debiting an L2 address's balance to be relayed to the L1
+/// bridge — the mirror image of [`DepositTransaction`]. Unlike a deposit,
+/// there's no L1 inclusion already vouching for it, so [`apply_transactions`]
+/// checks `source` can actually afford `amount`; a withdrawal it can't cover
+/// fails atomically (the source account is left untouched) rather than
+/// debiting a partial amount, and — same as an invalid transaction — is
+/// simply excluded rather than aborting the whole bundle. The debited
+/// balance is burned rather than escrowed anywhere on L2: the L1 bridge
+/// contract is the only place that balance needs to exist once withdrawn.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WithdrawalTransaction {
+    pub source: EvmAddress,
+    pub amount: PU256,
+}
+
+/// The account state a [`SystemTx`] reads and writes, decoupled from any
+/// particular [`Database`] so applying one is just two calls rather than a
+/// hand-rolled read/mutate/commit/record dance. "Ordered" the same way
+/// [`EvmStateLog`] is: whoever implements this is responsible for applying
+/// reads and writes in the order a [`SystemTx`] issues them.
+pub trait OrderedRwLog {
+    /// Reads `address`'s current account info, or `None` if it doesn't
+    /// exist.
+    fn read_account(&mut self, address: EvmAddress) -> Result<Option<AccountInfo>, SystemTxError>;
+
+    /// Commits `info` as `address`'s new account state, creating the
+    /// account if it didn't already exist, and records the write in the
+    /// log.
+    fn write_account(&mut self, address: EvmAddress, info: AccountInfo);
+}
+
+/// Errors [`SystemTx::apply`] can report. Same treatment as an invalid
+/// [`EvmTransaction`]: [`apply_transactions`] simply excludes a system
+/// transaction that fails rather than aborting the whole bundle.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SystemTxError {
+    /// The account this system transaction needed to read either doesn't
+    /// exist or couldn't be read from the underlying store.
+    #[error("account could not be read")]
+    UnreadableAccount,
+    /// A withdrawal (or similar debit) for more than the account's balance.
+    #[error("insufficient balance to cover the debit")]
+    InsufficientBalance,
+}
+
+/// A transaction authorized by L1/DA inclusion rather than a signature —
+/// [`DepositTransaction`], [`WithdrawalTransaction`], and any future
+/// gas-free system transaction (e.g. sequencer bonding) — applied directly
+/// to account state without ever going through revm's gas metering.
+pub trait SystemTx {
+    /// Applies this system transaction's effect to `log`.
+    fn apply(&self, log: &mut impl OrderedRwLog) -> Result<(), SystemTxError>;
+}
+
+impl SystemTx for DepositTransaction {
+    fn apply(&self, log: &mut impl OrderedRwLog) -> Result<(), SystemTxError> {
+        let mut info = log.read_account(self.target)?.unwrap_or_default();
+        info.balance = info.balance.saturating_add(U256::from_limbs(self.amount.0));
+        log.write_account(self.target, info);
+        Ok(())
+    }
+}
+
+impl SystemTx for WithdrawalTransaction {
+    fn apply(&self, log: &mut impl OrderedRwLog) -> Result<(), SystemTxError> {
+        let mut info = log
+            .read_account(self.source)?
+            .ok_or(SystemTxError::UnreadableAccount)?;
+        let amount = U256::from_limbs(self.amount.0);
+        if info.balance < amount {
+            return Err(SystemTxError::InsufficientBalance);
+        }
+        info.balance -= amount;
+        log.write_account(self.source, info);
+        Ok(())
+    }
+}
+
+/// Adapts a live [`Database`] + [`DatabaseCommit`] and the bundle's running
+/// log into the [`OrderedRwLog`] a [`SystemTx`] needs. Used only by
+/// [`apply_transactions`] and its siblings — host code applying a system
+/// transaction on its own has no reason to reach for this directly.
+struct DbBackedLog<'a, D> {
+    db: &'a mut D,
+    log: &'a mut HashMap<EvmAddress, AccountLogEntry>,
+    spec_id: SpecId,
+}
+
+impl<D: Database + DatabaseCommit> OrderedRwLog for DbBackedLog<'_, D> {
+    fn read_account(&mut self, address: EvmAddress) -> Result<Option<AccountInfo>, SystemTxError> {
+        self.db
+            .basic(address::to_revm(address))
+            .map_err(|_| SystemTxError::UnreadableAccount)
+    }
+
+    fn write_account(&mut self, address: EvmAddress, info: AccountInfo) {
+        let mut account: Account = info.into();
+        account.mark_touch();
+
+        let mut state = RevmHashMap::new();
+        state.insert(address::to_revm(address), account);
+        self.db.commit(state.clone());
+        record_commit(self.log, state, self.spec_id);
+    }
+}
+
+/// Applies each of `txs` (any [`SystemTx`]) against `db` in order, folding
+/// its effect into `log` — a new system transaction type only needs a
+/// [`SystemTx`] impl to slot into this, not a new loop in
+/// [`apply_transactions`]. A transaction whose `apply` fails is simply
+/// skipped, same as an invalid [`EvmTransaction`] would be.
+fn apply_system_txs<T: SystemTx, D: Database + DatabaseCommit>(
+    txs: &[T],
+    db: &mut D,
+    log: &mut HashMap<EvmAddress, AccountLogEntry>,
+    spec_id: SpecId,
+) {
+    for tx in txs {
+        let mut rw_log = DbBackedLog {
+            db: &mut *db,
+            log: &mut *log,
+            spec_id,
+        };
+        let _ = tx.apply(&mut rw_log);
+    }
+}
+
+/// Computes the next block's base fee from its parent's gas usage, per
+/// [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+///
+/// The parent's gas target is half its gas limit: usage above the target
+/// pushes the base fee up, usage below it pushes the base fee down, and
+/// usage exactly at the target leaves it unchanged. A block builder calls
+/// this once it knows the parent's actual gas usage, to set
+/// `BlockEnv.basefee` for the block it's assembling next.
+pub fn next_base_fee(parent_gas_used: u64, parent_gas_limit: u64, parent_base_fee: U256) -> U256 {
+    let gas_target = parent_gas_limit / ELASTICITY_MULTIPLIER;
+
+    if parent_gas_used == gas_target {
+        return parent_base_fee;
+    }
+
+    if parent_gas_used > gas_target {
+        let gas_used_delta = parent_gas_used - gas_target;
+        let base_fee_delta = std::cmp::max(
+            U256::from(1),
+            parent_base_fee.saturating_mul(U256::from(gas_used_delta))
+                / U256::from(gas_target)
+                / U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR),
+        );
+        parent_base_fee.saturating_add(base_fee_delta)
+    } else {
+        let gas_used_delta = gas_target - parent_gas_used;
+        let base_fee_delta = parent_base_fee.saturating_mul(U256::from(gas_used_delta))
+            / U256::from(gas_target)
+            / U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+        parent_base_fee.saturating_sub(base_fee_delta)
+    }
+}
+
+/// Filters `txs` down to, for each sender, the contiguous prefix of its
+/// nonce chain starting at its current on-chain nonce, walking `txs` in
+/// order: a transaction is kept only if its nonce is exactly the next one
+/// that sender's chain needs. Anything past a gap in the chain is dropped,
+/// to be resubmitted in a later block. Only the first transaction using a
+/// given nonce can extend the chain; a duplicate or out-of-order nonce is
+/// dropped on its own, and a later transaction carrying the next needed
+/// nonce is still kept.
+pub fn filter_transactions<DB: Database>(
+    txs: &[(EvmAddress, EvmTransaction)],
+    db: &mut DB,
+) -> Result<Vec<(EvmAddress, EvmTransaction)>, DB::Error> {
+    let mut next_nonce: HashMap<EvmAddress, u64> = HashMap::new();
+    let mut out = Vec::new();
+
+    for (sender, tx) in txs {
+        let expected = match next_nonce.get(sender) {
+            Some(n) => *n,
+            None => {
+                db.basic(address::to_revm(*sender))?
+                    .unwrap_or_default()
+                    .nonce
+            }
+        };
+
+        if tx.nonce() == expected {
+            out.push((*sender, tx.clone()));
+            next_nonce.insert(*sender, expected + 1);
+        } else {
+            next_nonce.entry(*sender).or_insert(expected);
+        }
+    }
+
+    Ok(out)
+}
+
+/// How a standalone transaction's execution concluded.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TxOutcome {
+    /// The call succeeded.
+    Success,
+    /// A `REVERT` opcode fired with the given (possibly ABI-encoded reason)
+    /// output bytes, without spending all the gas.
+    Reverted(Vec<u8>),
+    /// Execution halted before producing any return data — most commonly
+    /// running out of gas, but also e.g. a stack under/overflow.
+    Halted(Halt),
+}
+
+/// The result of running one transaction via [`run_standalone`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TxReceipt {
+    pub gas_used: u64,
+    pub outcome: TxOutcome,
+    /// The gas price actually paid, for a block explorer to report: the
+    /// transaction's own `gas_price` for a legacy transaction, or
+    /// `min(max_fee_per_gas, block.basefee + max_priority_fee_per_gas)` for
+    /// an EIP-1559 one, per [`revm::primitives::Env::effective_gas_price`].
+    pub effective_gas_price: PU256,
+}
+
+/// A commitment to `receipts` standing in for the receipts root Ethereum
+/// blocks commit to, paired with [`crate::bundle::transactions_root`]'s
+/// transactions root. This crate has no Merkle-Patricia trie implementation
+/// yet (see [`crate::trie`]), so this builds [`MerkleTree`]'s binary tree
+/// instead, over each receipt's Keccak256 digest, in the order given — close
+/// enough to bind a block to its exact receipts, even though it isn't
+/// byte-for-byte the RLP-keyed MPT a canonical Ethereum client would build.
+///
+/// Panics if `receipts` is empty, same as [`MerkleTree::build`].
+pub fn receipts_root(receipts: &[TxReceipt]) -> H256 {
+    let leaves: Vec<H256> = receipts
+        .iter()
+        .map(|receipt| {
+            let encoded = bincode::serialize(receipt).expect("TxReceipt is always serializable");
+            H256::from(Keccak256::digest(&encoded).as_ref())
+        })
+        .collect();
+    MerkleTree::commit(&leaves)
+}
+
+/// Errors raised while running a transaction via [`run_standalone`].
+#[derive(Debug, thiserror::Error)]
+pub enum TxError<DBError> {
+    /// The transaction's own gas limit exceeds the block's gas limit — it
+    /// could never fit in this block, regardless of what it does.
+    #[error("transaction gas limit {tx_gas_limit} exceeds block gas limit {block_gas_limit}")]
+    GasLimitExceedsBlock {
+        tx_gas_limit: u64,
+        block_gas_limit: u64,
+    },
+    /// The transaction's effective gas price (see
+    /// [`revm::primitives::Env::effective_gas_price`]) is zero, and
+    /// `config.allow_zero_gas_price` forbids that.
+    #[error("transaction has a zero effective gas price, which this rollup does not allow")]
+    ZeroGasPriceForbidden,
+    /// revm rejected or failed to run the transaction.
+    #[error(transparent)]
+    Evm(#[from] EVMError<DBError>),
+}
+
+/// Runs `tx` against `db` on its own, without committing any state, and
+/// reports how it concluded.
+///
+/// Unlike [`apply_transactions`], this doesn't affect `db` or produce a
+/// log — it's for a sequencer wanting to preview a transaction, e.g. to
+/// surface "execution reverted: `<reason>`" before including it in a
+/// bundle. A `REVERT` carries its output bytes; running out of gas (or any
+/// other halt) carries none, and is reported as [`TxOutcome::Halted`]
+/// rather than conflated with a reverted call.
+///
+/// Rejects `tx` outright, without running it, if its gas limit exceeds
+/// `block.gas_limit` — equal to the block limit is fine, only over it is
+/// rejected.
+///
+/// Also rejects `tx` if `caller` has non-empty code, per EIP-3607 (a
+/// contract account can't hold a valid EOA signature) — revm enforces this
+/// itself, surfaced here as [`TxError::Evm`] wrapping
+/// `InvalidTransaction::RejectCallerWithCode`.
+pub fn run_standalone<DB>(
+    caller: EvmAddress,
+    tx: &EvmTransaction,
+    block: BlockEnv,
+    config: &RollupConfig,
+    db: &mut DB,
+) -> Result<TxReceipt, TxError<DB::Error>>
+where
+    DB: Database,
+{
+    let block_gas_limit = u64::try_from(block.gas_limit).unwrap_or(u64::MAX);
+    if tx.gas_limit() > block_gas_limit {
+        return Err(TxError::GasLimitExceedsBlock {
+            tx_gas_limit: tx.gas_limit(),
+            block_gas_limit,
+        });
+    }
+
+    let mut evm: EVM<&mut DB> = EVM::new();
+    evm.env.block = block;
+    tx.add_to_env(caller, &mut evm.env.tx);
+    configure_from_rollup(&mut evm.env, config);
+    let effective_gas_price = crate::convert::u256_from_revm(evm.env.effective_gas_price());
+    if !config.allow_zero_gas_price && effective_gas_price.is_zero() {
+        return Err(TxError::ZeroGasPriceForbidden);
+    }
+    evm.db = Some(db);
+
+    let result_and_state = evm.transact()?;
+    let gas_used = result_and_state.result.gas_used();
+    let outcome = match result_and_state.result {
+        ExecutionResult::Success { .. } => TxOutcome::Success,
+        ExecutionResult::Revert { output, .. } => TxOutcome::Reverted(output.to_vec()),
+        ExecutionResult::Halt { reason, .. } => TxOutcome::Halted(reason),
+    };
+    Ok(TxReceipt {
+        gas_used,
+        outcome,
+        effective_gas_price,
+    })
+}
+
+/// Applies `config`'s chain ID, spec, and contract code-size limit to `env`.
+/// `env.tx` must already be populated (e.g. via
+/// [`EvmTransaction::add_to_env`](crate::tx::EvmTransaction::add_to_env))
+/// before this runs: when `config.allow_pre_155` is set, this clears
+/// `env.tx.chain_id` so revm skips its own chain-ID check — the same way it
+/// already treats a transaction that never set one — rather than requiring
+/// every transaction to match `config.chain_id` exactly.
+///
+/// Shared by [`run_standalone`] and [`run_standalone_traced`].
+pub(crate) fn configure_from_rollup(env: &mut Env, config: &RollupConfig) {
+    env.cfg.chain_id = config.chain_id;
+    env.cfg.spec_id = config.spec_id;
+    env.cfg.limit_contract_code_size = Some(config.max_code_size);
+    if config.allow_pre_155 {
+        env.tx.chain_id = None;
+    }
+}
+
+/// EIP-7702's delegation designator: the 23 bytes (`0xef0100` followed by
+/// the delegate's address) a real client installs in an authority's account
+/// code in place of actual bytecode, signaling "treat calls to this account
+/// as calls to `address`".
+const DELEGATION_DESIGNATOR_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Applies `authorizations` to `db`: for each entry whose
+/// [`SetCodeAuthorization::verify`] passes, installs the EIP-7702 delegation
+/// designator (`0xef0100` followed by [`SetCodeAuthorization::address`])
+/// into [`SetCodeAuthorization::authority`]'s account code, and bumps its
+/// nonce — exactly what a real client's authorization-processing step does,
+/// before the transaction itself runs. An authorization that fails to
+/// verify is skipped rather than failing the whole transaction, per
+/// EIP-7702.
+///
+/// revm 3.3 has no native EIP-7702 support: no `TxEnv::authorization_list`
+/// for [`EvmTransaction::add_to_env`] to populate, and no delegation-aware
+/// call dispatch in its interpreter. This only goes as far as installing
+/// the designator bytes in state the same way a real client would — a later
+/// `CALL` into `authority` won't actually execute `address`'s code the way
+/// EIP-7702 intends, since revm's interpreter has no special-casing for the
+/// `0xef01` prefix and will simply halt on what it sees as an invalid
+/// opcode.
+pub fn apply_set_code_authorizations<DB: Database + DatabaseCommit>(
+    authorizations: &[SetCodeAuthorization],
+    db: &mut DB,
+) -> Result<(), DB::Error> {
+    let mut state = RevmHashMap::new();
+    for auth in authorizations {
+        if !auth.verify() {
+            continue;
+        }
+
+        let address = address::to_revm(auth.authority);
+        let mut info = db.basic(address)?.unwrap_or_default();
+        info.nonce += 1;
+
+        let mut code = DELEGATION_DESIGNATOR_PREFIX.to_vec();
+        code.extend_from_slice(auth.address.as_bytes());
+        info.code_hash = B256::from(Keccak256::digest(&code).as_ref());
+        info.code = Some(Bytecode::new_raw(code.into()));
+
+        let mut account = Account::from(info);
+        account.mark_touch();
+        state.insert(address, account);
+    }
+    db.commit(state);
+    Ok(())
+}
+
+/// An opcode-level execution trace, for diagnosing why a transaction
+/// produced an unexpected outcome. Host-only: compiled out of the guest
+/// entirely, since the guest has no use for human-debugging traces and they'd
+/// only bloat proving.
+#[cfg(feature = "host")]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OpcodeTrace {
+    /// The opcodes executed, in order, one entry per interpreter step.
+    pub opcodes: Vec<u8>,
+}
+
+/// A revm [`revm::Inspector`] that records every opcode executed into an
+/// [`OpcodeTrace`].
+#[cfg(feature = "host")]
+#[derive(Debug, Default)]
+pub struct TracingInspector {
+    trace: OpcodeTrace,
+}
+
+#[cfg(feature = "host")]
+impl TracingInspector {
+    /// Consumes this inspector, returning the trace it recorded.
+    pub fn into_trace(self) -> OpcodeTrace {
+        self.trace
+    }
+}
+
+#[cfg(feature = "host")]
+impl<DB: Database> revm::Inspector<DB> for TracingInspector {
+    fn step(
+        &mut self,
+        interp: &mut revm::interpreter::Interpreter,
+        _data: &mut revm::EVMData<'_, DB>,
+    ) -> revm::interpreter::InstructionResult {
+        self.trace.opcodes.push(interp.current_opcode());
+        revm::interpreter::InstructionResult::Continue
+    }
+}
+
+/// Runs `tx` exactly as [`run_standalone`] does, but with a [`TracingInspector`]
+/// attached, returning the opcode trace alongside the receipt. Host-only,
+/// for diagnosing why a transaction produced an unexpected log.
+#[cfg(feature = "host")]
+pub fn run_standalone_traced<DB>(
+    caller: EvmAddress,
+    tx: &EvmTransaction,
+    block: BlockEnv,
+    config: &RollupConfig,
+    db: &mut DB,
+) -> Result<(TxReceipt, OpcodeTrace), TxError<DB::Error>>
+where
+    DB: Database,
+{
+    let block_gas_limit = u64::try_from(block.gas_limit).unwrap_or(u64::MAX);
+    if tx.gas_limit() > block_gas_limit {
+        return Err(TxError::GasLimitExceedsBlock {
+            tx_gas_limit: tx.gas_limit(),
+            block_gas_limit,
+        });
+    }
+
+    let mut evm: EVM<&mut DB> = EVM::new();
+    evm.env.block = block;
+    tx.add_to_env(caller, &mut evm.env.tx);
+    configure_from_rollup(&mut evm.env, config);
+    let effective_gas_price = crate::convert::u256_from_revm(evm.env.effective_gas_price());
+    if !config.allow_zero_gas_price && effective_gas_price.is_zero() {
+        return Err(TxError::ZeroGasPriceForbidden);
+    }
+    evm.db = Some(db);
+
+    // `EVM::inspect` takes its inspector by value and doesn't hand it back,
+    // so it's passed a `&mut` here (which `Inspector` is auto-implemented
+    // for) to keep `inspector` ourselves and read its trace off afterwards.
+    let mut inspector = TracingInspector::default();
+    let result_and_state = evm.inspect(&mut inspector)?;
+    let gas_used = result_and_state.result.gas_used();
+    let outcome = match result_and_state.result {
+        ExecutionResult::Success { .. } => TxOutcome::Success,
+        ExecutionResult::Revert { output, .. } => TxOutcome::Reverted(output.to_vec()),
+        ExecutionResult::Halt { reason, .. } => TxOutcome::Halted(reason),
+    };
+    Ok((
+        TxReceipt {
+            gas_used,
+            outcome,
+            effective_gas_price,
+        },
+        inspector.into_trace(),
+    ))
+}
+
+fn key_to_h256(key: revm::primitives::U256) -> H256 {
+    H256::from(key.to_be_bytes::<32>())
+}
+
+/// Folds one touched account's post-execution state into its log entry.
+/// Shared by [`record_commit`] and [`apply_state_to_log`], which differ only
+/// in how they locate (or create) the entry to fold into.
+fn fold_touched_account(
+    entry: &mut AccountLogEntry,
+    account: &Account,
+    clears_empty_accounts: bool,
+) {
+    entry.created |= account.is_created();
+
+    if clears_empty_accounts && account.is_empty() {
+        entry.info = Access::Write(None);
+        entry.code = None;
+        return;
+    }
+    entry.info = Access::Write(Some(account.info.clone()));
+
+    if let Some(bytecode) = &account.info.code {
+        let code = bytecode.original_bytes().to_vec();
+        if !code.is_empty() {
+            entry.code = Some(Access::Write(Some(code)));
+        }
+    }
+
+    for (slot, value) in &account.storage {
+        let key = key_to_h256(*slot);
+        let written = Access::Write(Some(PU256(value.present_value().into_limbs())));
+        match entry.storage.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, existing)) => *existing = written,
+            None => entry.storage.push((key, written)),
+        }
+    }
+}
+
+/// Folds one transaction's post-execution state into the bundle's running
+/// log, journaling any newly-deployed code as a separate code-write entry
+/// so that a later transaction in the same bundle can have its
+/// `code_by_hash` read verified against it.
+///
+/// Before [EIP-161](https://eips.ethereum.org/EIPS/eip-161) (activated at
+/// Spurious Dragon), touched empty accounts stay in state; from Spurious
+/// Dragon on, they're deleted. revm's journal doesn't apply this itself —
+/// it returns every touched account regardless of spec — so `spec_id`
+/// decides here whether an empty touched account is logged as a deletion.
+pub(crate) fn record_commit(
+    log: &mut HashMap<EvmAddress, AccountLogEntry>,
+    state: RevmHashMap<Address, Account>,
+    spec_id: SpecId,
+) {
+    let clears_empty_accounts = SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON);
+
+    for (addr, account) in state {
+        if !account.is_touched() {
+            continue;
+        }
+        let address = address::from_revm(addr);
+        let entry = log.entry(address).or_insert_with(|| AccountLogEntry {
+            address,
+            info: Access::Write(None),
+            code: None,
+            storage: vec![],
+            storage_root: None,
+            created: false,
+        });
+        fold_touched_account(entry, &account, clears_empty_accounts);
+    }
+}
+
+/// Folds a transaction's access list into `log` as [`Access::Read`]s, for
+/// any address or slot [`record_commit`] didn't already record a write
+/// for. An access-list entry pre-warms its target for gas purposes (see
+/// `an_access_list_entry_pre_warms_exactly_the_slot_it_names`) even when
+/// execution never actually reads it, but it was still charged and loaded —
+/// the log needs to account for that too, or a re-verifier checking it
+/// against its own state would find an access the log never mentions.
+///
+/// An address with nothing behind it is recorded as `Access::Read(None)`
+/// for its info, and the same for each of its listed slots — there's
+/// nothing to load, but the access list still named it.
+///
+/// Reads `db` directly rather than the transaction's own execution result,
+/// so this must run after `db` already has the transaction's own writes
+/// committed; harmless, since every address or slot reached here is by
+/// definition one the transaction never wrote. A read that errors is
+/// simply skipped, the same way an unreadable deposit target is in
+/// [`apply_transactions_with_access_limit`].
+fn record_access_list_reads<D: Database>(
+    log: &mut HashMap<EvmAddress, AccountLogEntry>,
+    access_list: &[(EvmAddress, Vec<PU256>)],
+    db: &mut D,
+) {
+    for (address, slots) in access_list {
+        if !log.contains_key(address) {
+            let info = match db.basic(address::to_revm(*address)) {
+                Ok(info) => info,
+                Err(_) => continue,
+            };
+            log.insert(
+                *address,
+                AccountLogEntry {
+                    address: *address,
+                    info: Access::Read(info),
+                    code: None,
+                    storage: vec![],
+                    storage_root: None,
+                    created: false,
+                },
+            );
+        }
+        let entry = log.get_mut(address).unwrap();
+        let account_exists = !matches!(entry.info, Access::Read(None) | Access::Write(None));
+
+        for slot in slots {
+            let key = key_to_h256(U256::from_limbs(slot.0));
+            if entry.storage.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            let value = if !account_exists {
+                None
+            } else {
+                match db.storage(address::to_revm(*address), U256::from_limbs(slot.0)) {
+                    Ok(value) => Some(PU256(value.into_limbs())),
+                    Err(_) => continue,
+                }
+            };
+            entry.storage.push((key, Access::Read(value)));
+        }
+    }
+}
+
+/// Folds `state` — a revm [`revm::primitives::State`] the caller already has
+/// in hand, e.g. from a [`revm::primitives::ResultAndState`] — directly into
+/// `log`, the same way [`record_commit`] would. Unlike `record_commit`, this
+/// takes `state` by reference and doesn't require ever pushing it through a
+/// [`revm::DatabaseCommit`]; useful on a host path that wants the resulting
+/// log but has its own reasons for committing `state` (or not committing it
+/// at all).
+///
+/// `log` may already contain entries; this updates or inserts into them in
+/// place, keeping `log.accounts` and each entry's `storage` in the strictly
+/// increasing order [`EvmStateLog::validate`] requires.
+pub fn apply_state_to_log(state: &revm::primitives::State, spec_id: SpecId, log: &mut EvmStateLog) {
+    let clears_empty_accounts = SpecId::enabled(spec_id, SpecId::SPURIOUS_DRAGON);
+
+    for (addr, account) in state {
+        if !account.is_touched() {
+            continue;
+        }
+        let address = address::from_revm(*addr);
+        let index = match log.accounts.binary_search_by_key(&address, |e| e.address) {
+            Ok(i) => i,
+            Err(i) => {
+                log.accounts.insert(
+                    i,
+                    AccountLogEntry {
+                        address,
+                        info: Access::Write(None),
+                        code: None,
+                        storage: vec![],
+                        storage_root: None,
+                        created: false,
+                    },
+                );
+                i
+            }
+        };
+        fold_touched_account(&mut log.accounts[index], account, clears_empty_accounts);
+        log.accounts[index].storage.sort_by_key(|(k, _)| *k);
+    }
+}
+
+/// Executes `txs` against `db` in order, crediting gas to `block`'s limit as
+/// it goes. The first transaction that would push the cumulative gas used
+/// over `block.gas_limit` is excluded, along with everything after it —
+/// those transactions are left for the next block. Returns which
+/// transactions were applied, plus the merged log of everything they
+/// touched.
+///
+/// `spec_id` controls, among other things, whether empty touched accounts
+/// are deleted from state (see [`record_commit`]); pass the spec your
+/// rollup is configured for, not necessarily the latest one revm knows.
+///
+/// `deposits` are credited to their target addresses before any of `txs`
+/// run, in order, creating the target account if it doesn't yet exist. A
+/// deposit whose target account can't be read from `db` is skipped, same as
+/// an invalid transaction would be.
+///
+/// `withdrawals` are debited from their source addresses after `deposits`
+/// but before `txs`, in order — see [`WithdrawalTransaction`] for how an
+/// unpayable withdrawal is handled.
+///
+/// `block.coinbase` is credited each transaction's priority fee, same as on
+/// mainnet — set it to the sequencer's configured fee recipient.
+///
+/// A transaction whose sender is itself absent from `db` isn't special-cased
+/// either: revm's own validation reads it as nonce 0, balance 0, same as any
+/// other fresh account, so a first-time sender's nonce-0 transaction is
+/// accepted if it can cover its own cost (e.g. via a preceding deposit) and
+/// excluded — not errored — otherwise.
+///
+/// `bundle_atomic` switches between this crate's two bundle-inclusion
+/// policies: `false` is the behavior described above (an invalid
+/// transaction is quietly excluded, everything else still applies); `true`
+/// makes the whole bundle all-or-nothing — the first transaction that fails
+/// validation, or that would push the block over its gas limit, aborts
+/// [`apply_transactions`] with [`BundleAbortedError`] and leaves `db`
+/// completely untouched, deposits and withdrawals included. Probing a
+/// bundle this way costs an extra in-memory overlay over `db` (see
+/// [`Overlay`]) so that nothing lands in `db` itself until every transaction
+/// is known to have succeeded.
+///
+/// `max_log_accesses` bounds the merged log the same way `block.gas_limit`
+/// bounds gas: once it's been reached, the next transaction is excluded
+/// (and left for the next block) rather than growing the log further,
+/// keeping proving cost and memory for a single block bounded. Pass
+/// `usize::MAX` for no cap.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_transactions<DB>(
+    deposits: &[DepositTransaction],
+    withdrawals: &[WithdrawalTransaction],
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    bundle_atomic: bool,
+    db: &mut DB,
+    max_log_accesses: usize,
+) -> Result<(TxTree, EvmStateLog), BundleAbortedError>
+where
+    DB: Database + DatabaseCommit,
+{
+    if !bundle_atomic {
+        return Ok(apply_transactions_unchecked(
+            deposits,
+            withdrawals,
+            txs,
+            block,
+            spec_id,
+            db,
+            max_log_accesses,
+        ));
+    }
+
+    let mut overlay = Overlay::new(db);
+    let (tree, log) = apply_transactions_unchecked(
+        deposits,
+        withdrawals,
+        txs,
+        block,
+        spec_id,
+        &mut overlay,
+        max_log_accesses,
+    );
+
+    match tree.includes.iter().position(|included| !included) {
+        Some(index) => Err(BundleAbortedError::InvalidTransaction { index }),
+        None => {
+            overlay.commit_into_base();
+            Ok((tree, log))
+        }
+    }
+}
+
+/// Errors raised by [`apply_transactions`] when `bundle_atomic` aborts a
+/// bundle.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BundleAbortedError {
+    /// `txs[index]` failed validation (bad nonce, insufficient balance,
+    /// ...), or didn't fit under the block's gas limit, and `bundle_atomic`
+    /// forbids excluding it and applying the rest.
+    #[error("transaction {index} was excluded from the bundle and bundle_atomic forbids that")]
+    InvalidTransaction { index: usize },
+}
+
+/// A thin in-memory overlay over a `base` database, used by
+/// [`apply_transactions`] to probe a whole bundle for validity under
+/// `bundle_atomic` before ever touching `base`: every write lands in
+/// `overlay` instead, so aborting partway through the bundle just means
+/// dropping `overlay` without ever calling `base`'s
+/// [`DatabaseCommit::commit`].
+struct Overlay<'a, DB> {
+    base: &'a mut DB,
+    overlay: RevmHashMap<Address, Account>,
+}
+
+impl<'a, DB> Overlay<'a, DB> {
+    fn new(base: &'a mut DB) -> Self {
+        Overlay {
+            base,
+            overlay: RevmHashMap::new(),
+        }
+    }
+}
+
+impl<'a, DB: DatabaseCommit> Overlay<'a, DB> {
+    /// Folds everything this overlay accumulated into `base`, once the
+    /// bundle it was probing is known to have succeeded in full.
+    fn commit_into_base(self) {
+        self.base.commit(self.overlay);
+    }
+}
+
+impl<'a, DB: Database> Database for Overlay<'a, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.overlay.get(&address) {
+            Some(account) => Ok(Some(account.info.clone())),
+            None => self.base.basic(address),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.base.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self
+            .overlay
+            .get(&address)
+            .and_then(|account| account.storage.get(&index))
+        {
+            Some(slot) => Ok(slot.present_value),
+            None => self.base.storage(address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.base.block_hash(number)
+    }
+}
+
+impl<'a, DB> DatabaseCommit for Overlay<'a, DB> {
+    fn commit(&mut self, changes: RevmHashMap<Address, Account>) {
+        self.overlay.extend(changes);
+    }
+}
+
+/// [`apply_transactions`]'s actual execution loop, run either directly
+/// against its caller's `db` (when `bundle_atomic` is `false`) or against a
+/// throwaway [`Overlay`] over it (when probing an atomic bundle) — this
+/// never fails outright, it only ever excludes individual transactions, the
+/// same way `apply_transactions` always did before `bundle_atomic` existed.
+#[allow(clippy::too_many_arguments)]
+fn apply_transactions_unchecked<DB>(
+    deposits: &[DepositTransaction],
+    withdrawals: &[WithdrawalTransaction],
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    db: &mut DB,
+    max_log_accesses: usize,
+) -> (TxTree, EvmStateLog)
+where
+    DB: Database + DatabaseCommit,
+{
+    let mut evm: EVM<&mut DB> = EVM::new();
+    evm.env.cfg.spec_id = spec_id;
+    evm.env.block = block.clone();
+    evm.db = Some(db);
+
+    let mut includes = vec![false; txs.len()];
+    let mut cumulative_gas: u64 = 0;
+    let mut log = HashMap::new();
+
+    apply_system_txs(deposits, evm.db.as_mut().unwrap(), &mut log, spec_id);
+    apply_system_txs(withdrawals, evm.db.as_mut().unwrap(), &mut log, spec_id);
+
+    for (i, (caller, tx)) in txs.iter().enumerate() {
+        if cumulative_gas.saturating_add(tx.gas_limit())
+            > u64::try_from(block.gas_limit).unwrap_or(u64::MAX)
+        {
+            break;
+        }
+        if count_log_accesses(&log) >= max_log_accesses {
+            break;
+        }
+
+        tx.add_to_env(*caller, &mut evm.env.tx);
+        let result_and_state = match evm.transact() {
+            Ok(r) => r,
+            // An invalid transaction (bad nonce, insufficient balance, ...)
+            // is simply excluded from this block rather than aborting the
+            // whole bundle; the sequencer is free to retry it later.
+            Err(_) => {
+                includes[i] = false;
+                continue;
+            }
+        };
+
+        evm.db
+            .as_mut()
+            .unwrap()
+            .commit(result_and_state.state.clone());
+        record_commit(&mut log, result_and_state.state, spec_id);
+        record_access_list_reads(&mut log, tx.access_list(), evm.db.as_mut().unwrap());
+        cumulative_gas += tx.gas_limit();
+        includes[i] = true;
+    }
+
+    let mut accounts: Vec<_> = log.into_values().collect();
+    accounts.sort_by_key(|e| e.address);
+    for entry in &mut accounts {
+        entry.storage.sort_by_key(|(k, _)| *k);
+    }
+
+    (
+        TxTree { includes },
+        EvmStateLog {
+            accounts,
+            sequencer_balances: vec![],
+        },
+    )
+}
+
+/// A storage slot more than one transaction in a bundle wrote to, paired
+/// with how many distinct transactions wrote it. See
+/// [`apply_transactions_with_conflicts`].
+#[cfg(feature = "host")]
+pub type StorageConflicts = Vec<((EvmAddress, H256), usize)>;
+
+/// Like [`apply_transactions`], but also reports storage slots more than
+/// one transaction in `txs` wrote to, as `(address, key)` paired with how
+/// many distinct transactions wrote it.
+///
+/// [`EvmStateLog::merge`] already resolves a write-write conflict correctly
+/// on its own (the last write wins) — this doesn't change that, it's purely
+/// informational, for a sequencer doing MEV analysis on its own bundle.
+/// Host-only: the guest has no use for it, and it shouldn't cost proving
+/// cycles.
+#[cfg(feature = "host")]
+pub fn apply_transactions_with_conflicts<DB>(
+    deposits: &[DepositTransaction],
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    db: &mut DB,
+) -> (TxTree, EvmStateLog, StorageConflicts)
+where
+    DB: Database + DatabaseCommit,
+{
+    let mut evm: EVM<&mut DB> = EVM::new();
+    evm.env.cfg.spec_id = spec_id;
+    evm.env.block = block.clone();
+    evm.db = Some(db);
+
+    let mut includes = vec![false; txs.len()];
+    let mut cumulative_gas: u64 = 0;
+    let mut log = HashMap::new();
+    let mut writer_counts: HashMap<(EvmAddress, H256), usize> = HashMap::new();
+
+    apply_system_txs(deposits, evm.db.as_mut().unwrap(), &mut log, spec_id);
+
+    for (i, (caller, tx)) in txs.iter().enumerate() {
+        if cumulative_gas.saturating_add(tx.gas_limit())
+            > u64::try_from(block.gas_limit).unwrap_or(u64::MAX)
+        {
+            break;
+        }
+
+        tx.add_to_env(*caller, &mut evm.env.tx);
+        let result_and_state = match evm.transact() {
+            Ok(r) => r,
+            Err(_) => {
+                includes[i] = false;
+                continue;
+            }
+        };
+
+        // Every slot a transaction touches ends up logged as a `Write` by
+        // `fold_touched_account` regardless of whether its value actually
+        // changed (e.g. writing back the value already there) — matching
+        // that here keeps a slot's reported writer count consistent with
+        // what the log itself will say was written to it.
+        for (addr, account) in &result_and_state.state {
+            let address = address::from_revm(*addr);
+            for slot in account.storage.keys() {
+                let key = key_to_h256(*slot);
+                *writer_counts.entry((address, key)).or_insert(0) += 1;
+            }
+        }
+
+        evm.db
+            .as_mut()
+            .unwrap()
+            .commit(result_and_state.state.clone());
+        record_commit(&mut log, result_and_state.state, spec_id);
+        cumulative_gas += tx.gas_limit();
+        includes[i] = true;
+    }
+
+    let mut accounts: Vec<_> = log.into_values().collect();
+    accounts.sort_by_key(|e| e.address);
+    for entry in &mut accounts {
+        entry.storage.sort_by_key(|(k, _)| *k);
+    }
+
+    let mut conflicts: Vec<_> = writer_counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .collect();
+    conflicts.sort_by_key(|(key, _)| *key);
+
+    (
+        TxTree { includes },
+        EvmStateLog {
+            accounts,
+            sequencer_balances: vec![],
+        },
+        conflicts,
+    )
+}
+
+/// Like [`apply_transactions`], but splits `txs` into a sequence of
+/// sub-trees instead of one, starting a fresh sub-tree whenever the current
+/// one's accumulated log would otherwise grow past `max_log_bytes`. Each
+/// sub-tree can be proved on its own and the results merged back together
+/// with [`EvmStateLog::merge`] — for bundles too large to execute inline in
+/// one guest run.
+///
+/// The encoded size of the accumulated log stands in for proof size, since
+/// this crate has no real cycle-counting guest to measure against yet.
+/// Crossing the budget ends the current sub-tree after the transaction that
+/// pushed it over, so a transaction's own log additions are never split
+/// across two sub-trees — even one whose log alone exceeds `max_log_bytes`
+/// still lands whole in its own sub-tree.
+pub fn apply_transactions_with_budget<DB>(
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    db: &mut DB,
+    max_log_bytes: usize,
+) -> Vec<(TxTree, EvmStateLog)>
+where
+    DB: Database + DatabaseCommit,
+{
+    let mut evm: EVM<&mut DB> = EVM::new();
+    evm.env.cfg.spec_id = spec_id;
+    evm.env.block = block.clone();
+    evm.db = Some(db);
+
+    let mut trees = Vec::new();
+    let mut includes = Vec::new();
+    let mut log: HashMap<EvmAddress, AccountLogEntry> = HashMap::new();
+    let mut cumulative_gas: u64 = 0;
+
+    for (caller, tx) in txs {
+        if cumulative_gas.saturating_add(tx.gas_limit())
+            > u64::try_from(block.gas_limit).unwrap_or(u64::MAX)
+        {
+            break;
+        }
+
+        tx.add_to_env(*caller, &mut evm.env.tx);
+        let result_and_state = match evm.transact() {
+            Ok(r) => r,
+            Err(_) => {
+                includes.push(false);
+                continue;
+            }
+        };
+
+        evm.db
+            .as_mut()
+            .unwrap()
+            .commit(result_and_state.state.clone());
+        record_commit(&mut log, result_and_state.state, spec_id);
+        cumulative_gas += tx.gas_limit();
+        includes.push(true);
+
+        if log_size_estimate(&log) > max_log_bytes {
+            trees.push(finish_tree(
+                std::mem::take(&mut includes),
+                std::mem::take(&mut log),
+            ));
+        }
+    }
+
+    if !includes.is_empty() || trees.is_empty() {
+        trees.push(finish_tree(includes, log));
+    }
+
+    trees
+}
+
+/// Sorts `log`'s entries and wraps them into the `(TxTree, EvmStateLog)`
+/// pair [`apply_transactions`] and [`apply_transactions_with_budget`] both
+/// return.
+fn finish_tree(
+    includes: Vec<bool>,
+    log: HashMap<EvmAddress, AccountLogEntry>,
+) -> (TxTree, EvmStateLog) {
+    let mut accounts: Vec<_> = log.into_values().collect();
+    accounts.sort_by_key(|e| e.address);
+    for entry in &mut accounts {
+        entry.storage.sort_by_key(|(k, _)| *k);
+    }
+    (
+        TxTree { includes },
+        EvmStateLog {
+            accounts,
+            sequencer_balances: vec![],
+        },
+    )
+}
+
+/// An estimate of how many bytes `log` would encode to, used to budget
+/// [`apply_transactions_with_budget`]'s sub-trees. Computed directly from
+/// unsorted entry references (ordering doesn't affect encoded size), so it
+/// doesn't pay for a sort on every transaction.
+fn log_size_estimate(log: &HashMap<EvmAddress, AccountLogEntry>) -> usize {
+    let accounts: Vec<&AccountLogEntry> = log.values().collect();
+    bincode::serialized_size(&accounts).unwrap_or(u64::MAX) as usize
+}
+
+/// The number of state accesses `log` holds: each touched account's info
+/// counts once, plus one more for each storage slot recorded under it.
+/// Counted directly rather than going through [`EvmStateLog::validate`]'s
+/// richer view, since [`apply_transactions_with_access_limit`] needs to
+/// check this after every transaction, not just once at the end.
+fn count_log_accesses(log: &HashMap<EvmAddress, AccountLogEntry>) -> usize {
+    log.values().map(|entry| 1 + entry.storage.len()).sum()
+}
+
+/// Errors raised by [`apply_transactions_with_access_limit`].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum AccessLimitError {
+    /// The accumulated log grew past `max` state accesses partway through
+    /// the bundle. Unlike [`apply_transactions_with_budget`], which starts a
+    /// fresh sub-tree once its own budget is crossed, this stops outright —
+    /// the caller is expected to fall back to delegating the bundle to a
+    /// separate sub-proof rather than keep growing one inline execution's
+    /// log without bound.
+    #[error("log exceeded {max} state accesses")]
+    ResourceExhausted { max: usize },
+}
+
+/// Like [`apply_transactions`], but stops and returns
+/// [`AccessLimitError::ResourceExhausted`] the moment the accumulated log
+/// would hold more than `max_accesses` state accesses, instead of letting
+/// it grow without bound.
+///
+/// A bundle executed inline can accumulate a log large enough to exhaust
+/// guest memory before it ever finishes; this gives a caller a cheap,
+/// deterministic signal to catch that ahead of time, so it can retry with
+/// the remaining transactions delegated to a separate sub-proof instead of
+/// running out of memory mid-proof.
+///
+/// Runs against an [`Overlay`] over `db`, the same one [`apply_transactions`]
+/// uses for its `bundle_atomic` path: tripping the limit leaves `db`
+/// completely untouched (deposits included), so a caller can retry the whole
+/// bundle — against a sub-proof, a higher limit, or whatever else — without
+/// first having to account for a partial commit.
+pub fn apply_transactions_with_access_limit<DB>(
+    deposits: &[DepositTransaction],
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    db: &mut DB,
+    max_accesses: usize,
+) -> Result<(TxTree, EvmStateLog), AccessLimitError>
+where
+    DB: Database + DatabaseCommit,
+{
+    let mut overlay = Overlay::new(db);
+    let result = apply_transactions_with_access_limit_unchecked(
+        deposits,
+        txs,
+        block,
+        spec_id,
+        &mut overlay,
+        max_accesses,
+    );
+    if result.is_ok() {
+        overlay.commit_into_base();
+    }
+    result
+}
+
+/// [`apply_transactions_with_access_limit`]'s actual execution loop, run
+/// against whatever `db` it's handed — always an [`Overlay`] in practice, so
+/// that a trip partway through never reaches the caller's real database.
+fn apply_transactions_with_access_limit_unchecked<DB>(
+    deposits: &[DepositTransaction],
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    db: &mut DB,
+    max_accesses: usize,
+) -> Result<(TxTree, EvmStateLog), AccessLimitError>
+where
+    DB: Database + DatabaseCommit,
+{
+    let mut evm: EVM<&mut DB> = EVM::new();
+    evm.env.cfg.spec_id = spec_id;
+    evm.env.block = block.clone();
+    evm.db = Some(db);
+
+    let mut includes = Vec::with_capacity(txs.len());
+    let mut log: HashMap<EvmAddress, AccountLogEntry> = HashMap::new();
+    let mut cumulative_gas: u64 = 0;
+
+    for deposit in deposits {
+        let db = evm.db.as_mut().unwrap();
+        let address = address::to_revm(deposit.target);
+        let info = match db.basic(address) {
+            Ok(info) => info.unwrap_or_default(),
+            Err(_) => continue,
+        };
+
+        let mut account: Account = info.into();
+        account.info.balance = account
+            .info
+            .balance
+            .saturating_add(U256::from_limbs(deposit.amount.0));
+        account.mark_touch();
+
+        let mut state = RevmHashMap::new();
+        state.insert(address, account);
+        db.commit(state.clone());
+        record_commit(&mut log, state, spec_id);
+
+        if count_log_accesses(&log) > max_accesses {
+            return Err(AccessLimitError::ResourceExhausted { max: max_accesses });
+        }
+    }
+
+    for (caller, tx) in txs {
+        if cumulative_gas.saturating_add(tx.gas_limit())
+            > u64::try_from(block.gas_limit).unwrap_or(u64::MAX)
+        {
+            break;
+        }
+
+        tx.add_to_env(*caller, &mut evm.env.tx);
+        let result_and_state = match evm.transact() {
+            Ok(r) => r,
+            Err(_) => {
+                includes.push(false);
+                continue;
+            }
+        };
+
+        evm.db
+            .as_mut()
+            .unwrap()
+            .commit(result_and_state.state.clone());
+        record_commit(&mut log, result_and_state.state, spec_id);
+        cumulative_gas += tx.gas_limit();
+        includes.push(true);
+
+        if count_log_accesses(&log) > max_accesses {
+            return Err(AccessLimitError::ResourceExhausted { max: max_accesses });
+        }
+    }
+
+    Ok(finish_tree(includes, log))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::{Eip1559Tx, LegacyTx, TxCommon};
+    use revm::db::InMemoryDB;
+    use revm::primitives::{create_address, AccountInfo, Bytecode};
+
+    fn tx(nonce: u64, gas_limit: u64, to: Option<EvmAddress>, data: Vec<u8>) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce,
+                gas_limit,
+                to,
+                value: PU256::zero(),
+                data,
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    fn tx_with_chain_id(
+        nonce: u64,
+        gas_limit: u64,
+        to: Option<EvmAddress>,
+        chain_id: u64,
+    ) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id,
+                nonce,
+                gas_limit,
+                to,
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    fn tx_with_value(
+        nonce: u64,
+        gas_limit: u64,
+        to: Option<EvmAddress>,
+        value: PU256,
+    ) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce,
+                gas_limit,
+                to,
+                value,
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    fn funded_db(sender: EvmAddress) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        db
+    }
+
+    #[test]
+    fn stops_at_the_block_gas_limit() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+
+        let txs: Vec<_> = (0..4)
+            .map(|n| {
+                (
+                    sender,
+                    tx(n, 21_000, Some(EvmAddress::repeat_byte(0xBB)), vec![]),
+                )
+            })
+            .collect();
+        let block = BlockEnv {
+            gas_limit: U256::from(3 * 21_000u64),
+            ..Default::default()
+        };
+
+        let (tree, _) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true, true, true, false]);
+    }
+
+    #[test]
+    fn a_deposit_to_a_fresh_address_creates_the_account_with_the_deposited_balance() {
+        let mut db = InMemoryDB::default();
+        let target = EvmAddress::repeat_byte(0xDD);
+        let deposits = vec![DepositTransaction {
+            target,
+            amount: PU256::from(1_000u64),
+        }];
+
+        let (_, log) = apply_transactions(
+            &deposits,
+            &[],
+            &[],
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == target)
+            .expect("deposit target is in the log");
+        let Access::Write(Some(info)) = &entry.info else {
+            panic!("expected an info write entry crediting the deposit");
+        };
+        assert_eq!(info.balance, U256::from(1_000u64));
+
+        let on_chain = db.basic(address::to_revm(target)).unwrap().unwrap();
+        assert_eq!(on_chain.balance, U256::from(1_000u64));
+    }
+
+    #[test]
+    fn a_brand_new_sender_funded_by_a_same_bundle_deposit_can_submit_its_nonce_zero_transaction() {
+        let mut db = InMemoryDB::default();
+        let sender = EvmAddress::repeat_byte(0xEE);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        // `sender` is absent from `db` — `db.basic` reports it as `None` —
+        // so this deposit is what brings it into existence, nonce 0,
+        // balance enough to cover the transaction's own gas.
+        let deposits = vec![DepositTransaction {
+            target: sender,
+            amount: PU256::from(1_000_000u64),
+        }];
+        let txs = vec![(sender, tx(0, 21_000, Some(recipient), vec![]))];
+
+        let (tree, _) = apply_transactions(
+            &deposits,
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+    }
+
+    #[test]
+    fn a_deposit_is_gas_free_and_does_not_count_against_the_block_gas_limit() {
+        let mut db = InMemoryDB::default();
+        let sender = EvmAddress::repeat_byte(0xEE);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let deposits = vec![DepositTransaction {
+            target: sender,
+            amount: PU256::from(1_000_000u64),
+        }];
+        let txs = vec![(sender, tx(0, 21_000, Some(recipient), vec![]))];
+        // The block's gas limit covers only the one EVM transaction — if
+        // the deposit (a `SystemTx`, applied outside revm's gas metering)
+        // consumed any of it, the transaction would be excluded.
+        let block = BlockEnv {
+            gas_limit: U256::from(21_000u64),
+            ..Default::default()
+        };
+
+        let (tree, log) = apply_transactions(
+            &deposits,
+            &[],
+            &txs,
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        let sender_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == sender)
+            .expect("sender is in the log, credited by the deposit then debited by the tx");
+        let Access::Write(Some(info)) = &sender_entry.info else {
+            panic!("expected a write to sender's info");
+        };
+        // The deposit itself never pays gas; what's missing from its
+        // 1_000_000 credit is entirely the transaction's own gas cost.
+        assert!(
+            info.balance < U256::from(1_000_000u64),
+            "expected the transaction's gas cost to be deducted from the deposited balance"
+        );
+    }
+
+    #[test]
+    fn a_brand_new_unfunded_sender_s_nonce_zero_transaction_is_excluded_for_insufficient_balance() {
+        let mut db = InMemoryDB::default();
+        let sender = EvmAddress::repeat_byte(0xEE);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        // No deposit this time: `sender` defaults to nonce 0, balance 0 —
+        // nonce 0 matches its first transaction, but it can't pay for gas.
+        let txs = vec![(sender, tx(0, 21_000, Some(recipient), vec![]))];
+
+        let (tree, _) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![false]);
+    }
+
+    #[test]
+    fn a_non_atomic_bundle_still_excludes_an_invalid_transaction_and_applies_the_rest() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let unfunded = EvmAddress::repeat_byte(0xEE);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = funded_db(sender);
+
+        let txs = vec![
+            (unfunded, tx(0, 21_000, Some(recipient), vec![])),
+            (
+                sender,
+                tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64)),
+            ),
+        ];
+
+        let (tree, _) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![false, true]);
+        let recipient_balance = db
+            .basic(address::to_revm(recipient))
+            .unwrap()
+            .unwrap()
+            .balance;
+        assert!(
+            !recipient_balance.is_zero(),
+            "sender's valid transfer should still have gone through"
+        );
+    }
+
+    #[test]
+    fn an_atomic_bundle_aborts_and_leaves_db_untouched_when_one_transaction_is_invalid() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let unfunded = EvmAddress::repeat_byte(0xEE);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = funded_db(sender);
+        let sender_balance_before = db.basic(address::to_revm(sender)).unwrap().unwrap().balance;
+
+        let txs = vec![
+            (unfunded, tx(0, 21_000, Some(recipient), vec![])),
+            (sender, tx(0, 21_000, Some(recipient), vec![])),
+        ];
+
+        let err = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            true,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap_err();
+
+        assert_eq!(err, BundleAbortedError::InvalidTransaction { index: 0 });
+        assert!(db.basic(address::to_revm(recipient)).unwrap().is_none());
+        assert_eq!(
+            db.basic(address::to_revm(sender)).unwrap().unwrap().balance,
+            sender_balance_before
+        );
+    }
+
+    #[test]
+    fn an_atomic_bundle_commits_normally_once_every_transaction_is_valid() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = funded_db(sender);
+        let txs = vec![(
+            sender,
+            tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64)),
+        )];
+
+        let (tree, _) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            true,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        assert!(!db
+            .basic(address::to_revm(recipient))
+            .unwrap()
+            .unwrap()
+            .balance
+            .is_zero());
+    }
+
+    #[test]
+    fn a_low_max_log_accesses_excludes_the_last_transaction_of_a_bundle() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let first_recipient = EvmAddress::repeat_byte(0xB1);
+        let second_recipient = EvmAddress::repeat_byte(0xB2);
+        let third_recipient = EvmAddress::repeat_byte(0xB3);
+        let mut db = funded_db(sender);
+
+        let txs = vec![
+            (sender, tx(0, 21_000, Some(first_recipient), vec![])),
+            (sender, tx(1, 21_000, Some(second_recipient), vec![])),
+            (sender, tx(2, 21_000, Some(third_recipient), vec![])),
+        ];
+
+        // Each transfer touches the shared sender, the shared coinbase (paid
+        // the priority fee), and one new recipient, so the log holds 3
+        // entries after the first transfer and 4 after the second. A cap of
+        // 4 leaves no room for the third transfer's new recipient.
+        let (tree, _) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            4,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true, true, false]);
+        assert!(db
+            .basic(address::to_revm(third_recipient))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn a_withdrawal_within_balance_debits_the_source_and_leaves_no_other_trace() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let withdrawals = vec![WithdrawalTransaction {
+            source: sender,
+            amount: PU256::from(1_000u64),
+        }];
+
+        let (_, log) = apply_transactions(
+            &[],
+            &withdrawals,
+            &[],
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(log.accounts.len(), 1);
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == sender)
+            .expect("the withdrawal's source is in the log");
+        let Access::Write(Some(info)) = &entry.info else {
+            panic!("expected an info write entry debiting the withdrawal");
+        };
+        assert_eq!(info.balance, U256::from(1_000_000_000_000u64 - 1_000));
+
+        let on_chain = db.basic(address::to_revm(sender)).unwrap().unwrap();
+        assert_eq!(on_chain.balance, U256::from(1_000_000_000_000u64 - 1_000));
+    }
+
+    #[test]
+    fn a_withdrawal_exceeding_balance_fails_atomically_and_leaves_the_source_untouched() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(500u64),
+                ..Default::default()
+            },
+        );
+        let withdrawals = vec![WithdrawalTransaction {
+            source: sender,
+            amount: PU256::from(1_000u64),
+        }];
+
+        let (_, log) = apply_transactions(
+            &[],
+            &withdrawals,
+            &[],
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert!(log.accounts.iter().all(|e| e.address != sender));
+
+        let on_chain = db.basic(address::to_revm(sender)).unwrap().unwrap();
+        assert_eq!(on_chain.balance, U256::from(500u64));
+    }
+
+    #[test]
+    fn apply_state_to_log_matches_the_database_commit_round_trip() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let block = BlockEnv::default();
+
+        let run = |db: &mut InMemoryDB| -> revm::primitives::ResultAndState {
+            let mut evm: EVM<&mut InMemoryDB> = EVM::new();
+            evm.env.cfg.spec_id = SpecId::LATEST;
+            evm.env.block = block.clone();
+            tx_with_value(0, 21_000, Some(recipient), PU256::from(100u64))
+                .add_to_env(sender, &mut evm.env.tx);
+            evm.db = Some(db);
+            evm.transact().unwrap()
+        };
+
+        // Path A: commit through `DatabaseCommit`, then fold the same state
+        // into a log via `record_commit`, exactly as `apply_transactions`
+        // does.
+        let mut committed_db = funded_db(sender);
+        let via_commit = run(&mut committed_db);
+        committed_db.commit(via_commit.state.clone());
+        let mut by_hashmap = HashMap::new();
+        record_commit(&mut by_hashmap, via_commit.state, SpecId::LATEST);
+        let mut accounts: Vec<_> = by_hashmap.into_values().collect();
+        accounts.sort_by_key(|e| e.address);
+        for entry in &mut accounts {
+            entry.storage.sort_by_key(|(k, _)| *k);
+        }
+        let via_commit_log = EvmStateLog {
+            accounts,
+            sequencer_balances: vec![],
+        };
+
+        // Path B: never commit anything — fold the same `ResultAndState`
+        // straight into a log via `apply_state_to_log`.
+        let mut uncommitted_db = funded_db(sender);
+        let via_direct = run(&mut uncommitted_db);
+        let mut via_direct_log = EvmStateLog::default();
+        apply_state_to_log(&via_direct.state, SpecId::LATEST, &mut via_direct_log);
+
+        assert_eq!(via_commit_log, via_direct_log);
+        assert!(!via_commit_log.accounts.is_empty());
+    }
+
+    #[test]
+    fn commit_journals_deployed_code_for_a_later_call_in_the_same_bundle() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(30_000_000u64),
+            ..Default::default()
+        };
+
+        // PUSH1 0xff PUSH1 0x00 MSTORE PUSH1 0x01 PUSH1 0x1f RETURN: deploys
+        // a contract whose runtime code is the single byte 0xff, enough to
+        // exercise the code-journaling path (empty code isn't journaled).
+        let init_code = hex::decode("60ff6000526001601ff3").unwrap();
+        let deploy = tx(0, 1_000_000, None, init_code);
+        let created = create_address(address::to_revm(sender), 0);
+
+        let call = tx(1, 1_000_000, Some(address::from_revm(created)), vec![]);
+
+        let txs = vec![(sender, deploy), (sender, call)];
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true, true]);
+
+        let created_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == address::from_revm(created))
+            .expect("created account is in the log");
+        let Some(Access::Write(Some(code))) = &created_entry.code else {
+            panic!("expected a code write entry for the created account");
+        };
+        assert!(!code.is_empty());
+
+        // The code that was journaled must match what code_by_hash would
+        // later resolve from the database directly.
+        let Access::Write(Some(info)) = &created_entry.info else {
+            panic!("expected an info write entry for the created account");
+        };
+        let from_db = db.code_by_hash(info.code_hash).unwrap();
+        assert_eq!(from_db.original_bytes().to_vec(), *code);
+    }
+
+    #[cfg(feature = "host")]
+    #[test]
+    fn traced_call_records_the_expected_opcode_sequence() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+
+        // PUSH1 0x01 PUSH1 0x02 ADD STOP: a trivial contract whose
+        // execution should record exactly these four opcodes, in order.
+        let code = hex::decode("600160020100").unwrap();
+        let contract = EvmAddress::repeat_byte(0xCC);
+        db.insert_account_info(
+            address::to_revm(contract),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let call = tx(0, 100_000, Some(contract), vec![]);
+
+        let (receipt, trace) =
+            run_standalone_traced(sender, &call, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+        assert_eq!(trace.opcodes, vec![0x60, 0x60, 0x01, 0x00]);
+    }
+
+    #[cfg(feature = "host")]
+    #[test]
+    fn apply_transactions_with_conflicts_reports_a_slot_two_transactions_wrote() {
+        let first_sender = EvmAddress::repeat_byte(0xAA);
+        let second_sender = EvmAddress::repeat_byte(0xBB);
+        let contract = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(first_sender);
+        db.insert_account_info(
+            address::to_revm(second_sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        // PUSH1 0x2a PUSH1 0x00 SSTORE STOP: writes 0x2a to slot 0.
+        let code = hex::decode("602a60005500").unwrap();
+        db.insert_account_info(
+            address::to_revm(contract),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let txs = vec![
+            (first_sender, tx(0, 100_000, Some(contract), vec![])),
+            (second_sender, tx(0, 100_000, Some(contract), vec![])),
+        ];
+
+        let (tree, _, conflicts) =
+            apply_transactions_with_conflicts(&[], &txs, block, SpecId::LATEST, &mut db);
+
+        assert_eq!(tree.includes, vec![true, true]);
+        assert_eq!(conflicts, vec![((contract, H256::zero()), 2)]);
+    }
+
+    #[test]
+    fn self_transfer_logs_only_the_senders_own_gas_and_nonce_change() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let starting_balance = db.basic(address::to_revm(sender)).unwrap().unwrap().balance;
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            // The sender is also the block's fee recipient, so the only
+            // account touched at all is the sender itself — isolating the
+            // self-transfer behavior from the unrelated fact that gas fees
+            // always touch some coinbase account. A nonzero base fee still
+            // burns something even though the priority fee comes straight
+            // back to the sender as coinbase.
+            coinbase: address::to_revm(sender),
+            basefee: U256::from(1u64),
+            ..Default::default()
+        };
+
+        let transfer = tx_with_value(0, 21_000, Some(sender), PU256::from(1_000u64));
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, transfer)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        // One account touched (sender is both caller and recipient), no
+        // storage, no code — nothing for a self-transfer to write beyond
+        // the sender's own post-gas balance and incremented nonce.
+        assert_eq!(log.accounts.len(), 1);
+        let entry = &log.accounts[0];
+        assert_eq!(entry.address, sender);
+        assert!(entry.code.is_none());
+        assert!(entry.storage.is_empty());
+
+        let Access::Write(Some(info)) = &entry.info else {
+            panic!("expected an info write for the sender");
+        };
+        assert_eq!(info.nonce, 1);
+        // The transferred value nets to zero (sent and received by the
+        // same account); only gas was actually spent.
+        assert!(info.balance < starting_balance);
+    }
+
+    #[test]
+    fn the_configured_coinbase_is_credited_the_transactions_priority_fee() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let coinbase = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            coinbase: address::to_revm(coinbase),
+            basefee: U256::from(1u64),
+            ..Default::default()
+        };
+
+        // max_fee_per_gas: 10, max_priority_fee_per_gas: 1, so against a
+        // basefee of 1 the effective gas price is capped at
+        // basefee + priority = 2, crediting coinbase exactly 1 wei per gas.
+        let transfer = tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64));
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, transfer)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        let coinbase_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == coinbase)
+            .expect("coinbase should be journaled");
+        let Access::Write(Some(info)) = &coinbase_entry.info else {
+            panic!("expected an info write crediting the coinbase");
+        };
+        assert_eq!(info.balance, U256::from(21_000u64));
+    }
+
+    #[test]
+    fn a_coinbase_sending_its_own_transaction_nets_its_debit_and_fee_credit_correctly() {
+        let coinbase = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = funded_db(coinbase);
+        let starting_balance = db
+            .basic(address::to_revm(coinbase))
+            .unwrap()
+            .unwrap()
+            .balance;
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            // The sender is also the block's own fee recipient, so its one
+            // log entry must reflect both what it paid (value sent plus
+            // the full gas cost) and what it got back (the priority fee,
+            // credited to coinbase) composed into a single coherent
+            // balance, not the fee credit double-counted or clobbering the
+            // debit.
+            coinbase: address::to_revm(coinbase),
+            basefee: U256::from(1u64),
+            ..Default::default()
+        };
+
+        // max_fee_per_gas: 10, max_priority_fee_per_gas: 1, so against a
+        // basefee of 1 the effective gas price is capped at
+        // basefee + priority = 2: 42_000 total gas cost, of which the
+        // 21_000 priority-fee portion returns to coinbase.
+        let transfer = tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64));
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(coinbase, transfer)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        // One log entry for coinbase: it's touched as both the sender and
+        // the fee recipient, but they're the same account, so both roles
+        // fold into that single entry.
+        let coinbase_entries: Vec<_> = log
+            .accounts
+            .iter()
+            .filter(|e| e.address == coinbase)
+            .collect();
+        assert_eq!(coinbase_entries.len(), 1);
+        let Access::Write(Some(info)) = &coinbase_entries[0].info else {
+            panic!("expected an info write for coinbase");
+        };
+        assert_eq!(info.nonce, 1);
+        assert_eq!(
+            info.balance,
+            starting_balance - U256::from(1_000u64) - U256::from(42_000u64) + U256::from(21_000u64)
+        );
+    }
+
+    #[test]
+    fn leaving_coinbase_unset_credits_the_zero_address_rather_than_the_sender() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = funded_db(sender);
+        let starting_balance = db.basic(address::to_revm(sender)).unwrap().unwrap().balance;
+        // `BlockEnv::default()` leaves `coinbase` at its default, the zero
+        // address — an operator who forgets to configure a fee recipient
+        // should have fees visibly pile up at the zero address in the log,
+        // not get silently misrouted back to the sender or dropped.
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            basefee: U256::from(1u64),
+            ..Default::default()
+        };
+        assert_eq!(block.coinbase, address::to_revm(EvmAddress::zero()));
+
+        let transfer = tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64));
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, transfer)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        let zero_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == EvmAddress::zero())
+            .expect("the zero address should be journaled as the fee recipient");
+        let Access::Write(Some(info)) = &zero_entry.info else {
+            panic!("expected an info write crediting the zero address");
+        };
+        assert_eq!(info.balance, U256::from(21_000u64));
+
+        let sender_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == sender)
+            .expect("sender should be journaled");
+        let Access::Write(Some(sender_info)) = &sender_entry.info else {
+            panic!("expected an info write for the sender");
+        };
+        // The sender only paid gas; nothing it spent was rerouted back to
+        // itself instead of to the zero address.
+        assert!(sender_info.balance < starting_balance);
+    }
+
+    #[test]
+    fn standalone_call_to_a_precompile_executes_it_and_charges_its_gas() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        // The identity precompile, address 0x...04.
+        let identity = EvmAddress::from_low_u64_be(4);
+        let call = tx(0, 100_000, Some(identity), vec![0xAB; 32]);
+
+        let receipt =
+            run_standalone(sender, &call, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+        // The identity precompile itself costs 15 + 3 * ceil(32 / 32) = 18
+        // gas on top of the 21_000 base cost of a call and the 32 * 16 = 512
+        // gas for its nonzero calldata — confirming it actually ran rather
+        // than being treated as a call to an empty account (which would cost
+        // only those 21_512).
+        assert!(receipt.gas_used > 21_000 + 32 * 16);
+    }
+
+    #[test]
+    fn an_access_list_entry_pre_warms_exactly_the_slot_it_names() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let contract = EvmAddress::repeat_byte(0xCC);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        // PUSH1 0x00 SLOAD STOP: a single SLOAD of slot 0.
+        let code = hex::decode("60005400").unwrap();
+        let contract_info = AccountInfo {
+            code: Some(Bytecode::new_raw(code.into())),
+            ..Default::default()
+        };
+
+        let access_listed_call = |slot: PU256| {
+            EvmTransaction::Eip1559(Eip1559Tx {
+                common: TxCommon {
+                    chain_id: 1,
+                    nonce: 0,
+                    gas_limit: 100_000,
+                    to: Some(contract),
+                    value: PU256::zero(),
+                    data: vec![],
+                },
+                max_fee_per_gas: PU256::from(10u64),
+                max_priority_fee_per_gas: PU256::from(1u64),
+                // Both calls list exactly one address and one storage key,
+                // so they pay the same intrinsic access-list cost — the
+                // only thing that can differ between them is whether the
+                // listed key happens to be the one the code actually reads.
+                access_list: vec![(contract, vec![slot])],
+            })
+        };
+
+        // Names the slot the code reads: that SLOAD should run warm.
+        let mut warm_db = funded_db(sender);
+        warm_db.insert_account_info(address::to_revm(contract), contract_info.clone());
+        let warm_receipt = run_standalone(
+            sender,
+            &access_listed_call(PU256::zero()),
+            block.clone(),
+            &RollupConfig::default(),
+            &mut warm_db,
+        )
+        .unwrap();
+
+        // Names an unrelated slot: the code's actual SLOAD of slot 0 is
+        // still cold.
+        let mut cold_db = funded_db(sender);
+        cold_db.insert_account_info(address::to_revm(contract), contract_info);
+        let cold_receipt = run_standalone(
+            sender,
+            &access_listed_call(PU256::one()),
+            block,
+            &RollupConfig::default(),
+            &mut cold_db,
+        )
+        .unwrap();
+
+        assert_eq!(warm_receipt.outcome, TxOutcome::Success);
+        assert_eq!(cold_receipt.outcome, TxOutcome::Success);
+        // Berlin's cold-vs-warm SLOAD gap is exactly 2_000 (2_100 cold,
+        // 100 warm); isolating it this way confirms the access list
+        // actually pre-warms the slot it names, not just any slot under
+        // the address it names.
+        assert_eq!(cold_receipt.gas_used - warm_receipt.gas_used, 2_000);
+    }
+
+    #[test]
+    fn an_access_listed_slot_that_execution_never_reads_still_appears_as_a_log_read() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let bystander = EvmAddress::repeat_byte(0xBD);
+        let recipient = EvmAddress::repeat_byte(0xCC);
+        let slot = PU256::from(7u64);
+        let value = U256::from(42u64);
+
+        let mut db = funded_db(sender);
+        let bystander_info = AccountInfo {
+            balance: U256::from(1_000u64),
+            ..Default::default()
+        };
+        db.insert_account_info(address::to_revm(bystander), bystander_info.clone());
+        db.insert_account_storage(address::to_revm(bystander), U256::from_limbs(slot.0), value)
+            .unwrap();
+
+        let call = EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 100_000,
+                to: Some(recipient),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            // `bystander` is never touched by the transfer itself — only
+            // named here — so the slot it lists is warmed but unread.
+            access_list: vec![(bystander, vec![slot])],
+        });
+
+        let (_, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, call)],
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == bystander)
+            .expect("bystander's access-list entry should appear in the log");
+        assert_eq!(entry.info, Access::Read(Some(bystander_info)));
+        assert_eq!(
+            entry.storage,
+            vec![(
+                key_to_h256(U256::from_limbs(slot.0)),
+                Access::Read(Some(PU256(value.into_limbs())))
+            )]
+        );
+    }
+
+    #[test]
+    fn an_access_listed_slot_on_an_absent_account_appears_as_a_log_read_of_none() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let absent = EvmAddress::repeat_byte(0xAB);
+        let recipient = EvmAddress::repeat_byte(0xCC);
+        let slot = PU256::from(3u64);
+
+        let mut db = funded_db(sender);
+
+        let call = EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 100_000,
+                to: Some(recipient),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![(absent, vec![slot])],
+        });
+
+        let (_, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, call)],
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == absent)
+            .expect("absent account's access-list entry should appear in the log");
+        assert_eq!(entry.info, Access::Read(None));
+        assert_eq!(
+            entry.storage,
+            vec![(key_to_h256(U256::from_limbs(slot.0)), Access::Read(None))]
+        );
+    }
+
+    #[test]
+    fn apply_transactions_journals_no_persistent_state_for_a_call_to_a_precompile() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            // Isolates the precompile call from the unrelated fact that gas
+            // fees always touch some coinbase account, same as
+            // `self_transfer_logs_only_the_senders_own_gas_and_nonce_change`.
+            coinbase: address::to_revm(sender),
+            ..Default::default()
+        };
+
+        let identity = EvmAddress::from_low_u64_be(4);
+        let call = tx(0, 100_000, Some(identity), vec![0xAB; 32]);
+
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, call)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        // revm still touches the precompile's account when it's called, so
+        // it shows up in the log too — but post-state-clearing (the spec in
+        // force here) a touched-and-still-empty account is recorded as a
+        // no-op "never existed" write, never as real persistent state.
+        assert_eq!(log.accounts.len(), 2);
+        let sender_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == sender)
+            .expect("sender should be journaled");
+        assert!(matches!(sender_entry.info, Access::Write(Some(_))));
+
+        let precompile_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == identity)
+            .expect("the precompile address should be touched and journaled");
+        assert_eq!(precompile_entry.info, Access::Write(None));
+        assert!(precompile_entry.code.is_none());
+        assert!(precompile_entry.storage.is_empty());
+    }
+
+    #[test]
+    fn standalone_accepts_a_transaction_from_an_eoa_sender() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let transfer = tx(0, 21_000, Some(EvmAddress::repeat_byte(0xBB)), vec![]);
+        let receipt =
+            run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn standalone_rejects_a_transaction_from_a_contract_sender_per_eip_3607() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = InMemoryDB::default();
+        // A sender with non-empty code can't hold a valid EOA signature, so
+        // revm's EIP-3607 check (on by default: `cfg.disable_eip3607` is
+        // `false` unless explicitly set) rejects it before execution.
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                code: Some(Bytecode::new_raw(vec![0x00].into())),
+                ..Default::default()
+            },
+        );
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let transfer = tx(0, 21_000, Some(EvmAddress::repeat_byte(0xBB)), vec![]);
+        let result = run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db);
+
+        assert!(matches!(
+            result,
+            Err(TxError::Evm(EVMError::Transaction(
+                revm::primitives::InvalidTransaction::RejectCallerWithCode
+            )))
+        ));
+    }
+
+    #[test]
+    fn zero_value_call_logs_no_balance_change_beyond_gas() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(recipient),
+            AccountInfo {
+                balance: U256::from(500u64),
+                ..Default::default()
+            },
+        );
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            // Isolate the zero-value call's own effect from the unrelated
+            // fact that gas fees always touch some coinbase account.
+            coinbase: address::to_revm(sender),
+            ..Default::default()
+        };
+
+        let call = tx_with_value(0, 21_000, Some(recipient), PU256::zero());
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, call)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        // The recipient is touched (it was the call's target) but a
+        // zero-value call can't have changed its balance, so its entry
+        // must carry its balance through unchanged rather than some
+        // spurious write.
+        let recipient_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == recipient)
+            .expect("recipient is touched by the call");
+        let Access::Write(Some(info)) = &recipient_entry.info else {
+            panic!("expected an info write for the recipient");
+        };
+        assert_eq!(info.balance, U256::from(500u64));
+        assert!(recipient_entry.storage.is_empty());
+    }
+
+    #[test]
+    fn post_158_spec_deletes_an_empty_touched_account() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let fresh = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(30_000_000u64),
+            ..Default::default()
+        };
+
+        let txs = vec![(sender, tx(0, 21_000, Some(fresh), vec![]))];
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == fresh)
+            .expect("touched account is in the log");
+        assert_eq!(entry.info, Access::Write(None));
+    }
+
+    #[test]
+    fn pre_158_spec_retains_an_empty_touched_account() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let fresh = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(30_000_000u64),
+            ..Default::default()
+        };
+
+        let txs = vec![(sender, tx(0, 21_000, Some(fresh), vec![]))];
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            block,
+            SpecId::FRONTIER,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == fresh)
+            .expect("touched account is in the log");
+        assert_eq!(entry.info, Access::Write(Some(AccountInfo::default())));
+    }
+
+    #[test]
+    fn standalone_revert_surfaces_the_revert_reason_bytes() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv::default();
+
+        // PUSH4 0xdeadbeef PUSH1 0x00 MSTORE PUSH1 0x04 PUSH1 0x1c REVERT
+        let init_code = hex::decode("63deadbeef6000526004601cfd").unwrap();
+        let reverting = tx(0, 1_000_000, None, init_code);
+
+        let receipt =
+            run_standalone(sender, &reverting, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(
+            receipt.outcome,
+            TxOutcome::Reverted(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+    }
+
+    #[test]
+    fn standalone_out_of_gas_reports_a_halt_with_no_return_data() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let looping = EvmAddress::repeat_byte(0xDD);
+        let mut db = funded_db(sender);
+        // JUMPDEST PUSH1 0x00 JUMP: an infinite loop that burns gas forever.
+        db.insert_account_info(
+            address::to_revm(looping),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(hex::decode("5b600056").unwrap().into())),
+                ..Default::default()
+            },
+        );
+        let block = BlockEnv::default();
+        let call = tx(0, 30_000, Some(looping), vec![]);
+
+        let receipt =
+            run_standalone(sender, &call, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert!(matches!(
+            receipt.outcome,
+            TxOutcome::Halted(Halt::OutOfGas(_))
+        ));
+    }
+
+    #[test]
+    fn filter_transactions_drops_everything_after_a_nonce_gap() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                nonce: 5,
+                ..Default::default()
+            },
+        );
+
+        let txs: Vec<_> = [5u64, 6, 8]
+            .into_iter()
+            .map(|n| (sender, tx(n, 21_000, None, vec![])))
+            .collect();
+
+        let filtered = filter_transactions(&txs, &mut db).unwrap();
+
+        assert_eq!(
+            filtered
+                .iter()
+                .map(|(_, tx)| tx.nonce())
+                .collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+    }
+
+    #[test]
+    fn standalone_accepts_a_tx_gas_limit_exactly_at_the_block_limit() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(21_000u64),
+            ..Default::default()
+        };
+        let call = tx(0, 21_000, Some(EvmAddress::repeat_byte(0xBB)), vec![]);
+
+        let receipt =
+            run_standalone(sender, &call, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn standalone_rejects_a_tx_gas_limit_one_over_the_block_limit() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(21_000u64),
+            ..Default::default()
+        };
+        let call = tx(0, 21_001, Some(EvmAddress::repeat_byte(0xBB)), vec![]);
+
+        let err =
+            run_standalone(sender, &call, block, &RollupConfig::default(), &mut db).unwrap_err();
+
+        assert!(matches!(
+            err,
+            TxError::GasLimitExceedsBlock {
+                tx_gas_limit: 21_001,
+                block_gas_limit: 21_000,
+            }
+        ));
+    }
+
+    #[test]
+    fn standalone_treats_the_default_unbounded_block_gas_limit_as_u64_max_not_an_error() {
+        // `BlockEnv::default().gas_limit` is `U256::MAX`, revm's own sentinel
+        // for "no block gas limit configured" — clamping it to `u64::MAX`
+        // here (rather than erroring on the narrowing conversion) is what
+        // keeps that sentinel meaning "unlimited" instead of rejecting every
+        // transaction run against a default `BlockEnv`.
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv::default();
+        let call = tx(0, 21_000, Some(EvmAddress::repeat_byte(0xBB)), vec![]);
+
+        let receipt =
+            run_standalone(sender, &call, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    fn eip1559_transfer(max_fee_per_gas: u64, max_priority_fee_per_gas: u64) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(EvmAddress::repeat_byte(0xBB)),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(max_fee_per_gas),
+            max_priority_fee_per_gas: PU256::from(max_priority_fee_per_gas),
+            access_list: vec![],
+        })
+    }
+
+    #[test]
+    fn standalone_reports_the_eip1559_effective_gas_price_capped_by_basefee_plus_priority() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let transfer = eip1559_transfer(100, 10);
+
+        // Below the max fee: basefee + priority wins.
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            basefee: U256::from(40u64),
+            ..Default::default()
+        };
+        let receipt =
+            run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db).unwrap();
+        assert_eq!(receipt.effective_gas_price, PU256::from(50u64));
+
+        // Above the max fee: the max fee caps it instead.
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            basefee: U256::from(95u64),
+            ..Default::default()
+        };
+        let receipt =
+            run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db).unwrap();
+        assert_eq!(receipt.effective_gas_price, PU256::from(100u64));
+    }
+
+    #[test]
+    fn standalone_rejects_a_zero_gas_price_legacy_transaction_by_default() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let free = EvmTransaction::Legacy(LegacyTx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(EvmAddress::repeat_byte(0xBB)),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            gas_price: PU256::zero(),
+            access_list: vec![],
+        });
+
+        let err = run_standalone(
+            sender,
+            &free,
+            BlockEnv::default(),
+            &RollupConfig::default(),
+            &mut db,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, TxError::ZeroGasPriceForbidden));
+    }
+
+    #[test]
+    fn standalone_accepts_a_zero_gas_price_legacy_transaction_once_allowed() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let free = EvmTransaction::Legacy(LegacyTx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(EvmAddress::repeat_byte(0xBB)),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            gas_price: PU256::zero(),
+            access_list: vec![],
+        });
+        let sponsored = RollupConfig {
+            allow_zero_gas_price: true,
+            ..RollupConfig::default()
+        };
+
+        let receipt =
+            run_standalone(sender, &free, BlockEnv::default(), &sponsored, &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+        assert_eq!(receipt.effective_gas_price, PU256::zero());
+    }
+
+    #[test]
+    fn standalone_accepts_an_eip1559_transaction_with_no_priority_fee_that_still_pays_basefee() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        // max_fee_per_gas == basefee, zero priority fee: the effective gas
+        // price is exactly the basefee, not zero, so this should be allowed
+        // even though zero-gas-price transactions are forbidden.
+        let transfer = eip1559_transfer(40, 0);
+        let block = BlockEnv {
+            basefee: U256::from(40u64),
+            ..Default::default()
+        };
+
+        let receipt =
+            run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.effective_gas_price, PU256::from(40u64));
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn standalone_reports_the_legacy_effective_gas_price_as_its_own_gas_price() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let legacy = EvmTransaction::Legacy(LegacyTx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(EvmAddress::repeat_byte(0xBB)),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            gas_price: PU256::from(100u64),
+            access_list: vec![],
+        });
+        let mut db = funded_db(sender);
+        // A legacy transaction's effective price ignores the basefee
+        // entirely, unlike an EIP-1559 one.
+        let block = BlockEnv {
+            basefee: U256::from(40u64),
+            ..Default::default()
+        };
+
+        let receipt =
+            run_standalone(sender, &legacy, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.effective_gas_price, PU256::from(100u64));
+    }
+
+    #[test]
+    fn standalone_accepts_a_shanghai_creation_gas_limit_exactly_at_the_eip3860_floor() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(10_000_000u64),
+            ..Default::default()
+        };
+
+        // 320 bytes of zeroed initcode: 53_000 base (EIP-2 creation) +
+        // 320 * 4 zero-byte data cost + 10 words * 2 (EIP-3860) = 54_300,
+        // exactly covered by this gas limit.
+        let data = vec![0u8; 320];
+        let create = tx(0, 54_300, None, data);
+
+        let receipt = run_standalone(
+            sender,
+            &create,
+            block,
+            &RollupConfig {
+                spec_id: SpecId::SHANGHAI,
+                ..RollupConfig::default()
+            },
+            &mut db,
+        )
+        .unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+        assert_eq!(receipt.gas_used, 54_300);
+    }
+
+    #[test]
+    fn standalone_rejects_a_shanghai_creation_gas_limit_one_under_the_eip3860_floor() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(10_000_000u64),
+            ..Default::default()
+        };
+
+        // Same 320-byte initcode as above, one gas short of the EIP-3860
+        // floor computed for it.
+        let data = vec![0u8; 320];
+        let create = tx(0, 54_299, None, data);
+
+        let err = run_standalone(
+            sender,
+            &create,
+            block,
+            &RollupConfig {
+                spec_id: SpecId::SHANGHAI,
+                ..RollupConfig::default()
+            },
+            &mut db,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            TxError::Evm(EVMError::Transaction(
+                revm::primitives::InvalidTransaction::CallGasCostMoreThanGasLimit
+            ))
+        ));
+    }
+
+    #[test]
+    fn standalone_accepts_a_shanghai_creation_with_initcode_exactly_at_the_eip3860_size_cap() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        // EIP-3860 caps initcode at 49_152 bytes; exactly at the cap is
+        // still allowed.
+        let data = vec![0u8; 49_152];
+        let create = tx(0, 1_000_000, None, data);
+
+        let receipt = run_standalone(
+            sender,
+            &create,
+            block,
+            &RollupConfig {
+                spec_id: SpecId::SHANGHAI,
+                ..RollupConfig::default()
+            },
+            &mut db,
+        )
+        .unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn standalone_rejects_a_shanghai_creation_with_initcode_one_byte_over_the_eip3860_size_cap() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        // One byte over the cap is rejected outright, before execution,
+        // regardless of how much gas the transaction offers.
+        let data = vec![0u8; 49_153];
+        let create = tx(0, 1_000_000, None, data);
+
+        let err = run_standalone(
+            sender,
+            &create,
+            block,
+            &RollupConfig {
+                spec_id: SpecId::SHANGHAI,
+                ..RollupConfig::default()
+            },
+            &mut db,
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            err,
+            TxError::Evm(EVMError::Transaction(
+                revm::primitives::InvalidTransaction::CreateInitcodeSizeLimit
+            ))
+        ));
+    }
+
+    #[test]
+    fn standalone_does_not_apply_the_eip3860_size_cap_before_shanghai() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        // Over the EIP-3860 cap, but pre-Shanghai specs don't enforce it at
+        // all.
+        let data = vec![0u8; 49_153];
+        let create = tx(0, 1_000_000, None, data);
+
+        let receipt = run_standalone(
+            sender,
+            &create,
+            block,
+            &RollupConfig {
+                spec_id: SpecId::MERGE,
+                ..RollupConfig::default()
+            },
+            &mut db,
+        )
+        .unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    /// Deploys `code` at a fresh address and returns the outcome of calling
+    /// it under `spec_id`, for tests that only care whether a given opcode
+    /// is gated in or out by the configured spec.
+    fn run_opcode_under_spec(code: &[u8], spec_id: SpecId) -> TxOutcome {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let target = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(target),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.to_vec().into())),
+                ..Default::default()
+            },
+        );
+        let block = BlockEnv::default();
+        let call = tx(0, 1_000_000, Some(target), vec![]);
+        let config = RollupConfig {
+            spec_id,
+            ..RollupConfig::default()
+        };
+
+        run_standalone(sender, &call, block, &config, &mut db)
+            .unwrap()
+            .outcome
+    }
+
+    #[test]
+    fn push0_halts_as_not_activated_before_shanghai() {
+        // PUSH0
+        let code = hex::decode("5f").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::MERGE);
+
+        assert!(matches!(outcome, TxOutcome::Halted(Halt::NotActivated)));
+    }
+
+    #[test]
+    fn push0_succeeds_from_shanghai_onward() {
+        // PUSH0
+        let code = hex::decode("5f").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::SHANGHAI);
+
+        assert_eq!(outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn tload_halts_as_not_activated_before_cancun() {
+        // PUSH1 0x00, TLOAD
+        let code = hex::decode("60005c").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::SHANGHAI);
+
+        assert!(matches!(outcome, TxOutcome::Halted(Halt::NotActivated)));
+    }
+
+    #[test]
+    fn tload_succeeds_from_cancun_onward() {
+        // PUSH1 0x00, TLOAD
+        let code = hex::decode("60005c").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::CANCUN);
+
+        assert_eq!(outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn tstore_halts_as_not_activated_before_cancun() {
+        // PUSH1 0x00, PUSH1 0x00, TSTORE
+        let code = hex::decode("600060005d").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::SHANGHAI);
+
+        assert!(matches!(outcome, TxOutcome::Halted(Halt::NotActivated)));
+    }
+
+    #[test]
+    fn tstore_succeeds_from_cancun_onward() {
+        // PUSH1 0x00, PUSH1 0x00, TSTORE
+        let code = hex::decode("600060005d").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::CANCUN);
+
+        assert_eq!(outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn mcopy_halts_as_not_activated_before_cancun() {
+        // PUSH1 0x00, PUSH1 0x00, PUSH1 0x00, MCOPY
+        let code = hex::decode("6000600060005e").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::SHANGHAI);
+
+        assert!(matches!(outcome, TxOutcome::Halted(Halt::NotActivated)));
+    }
+
+    #[test]
+    fn mcopy_succeeds_from_cancun_onward() {
+        // PUSH1 0x00, PUSH1 0x00, PUSH1 0x00, MCOPY
+        let code = hex::decode("6000600060005e").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::CANCUN);
+
+        assert_eq!(outcome, TxOutcome::Success);
+    }
+
+    #[test]
+    fn prevrandao_read_by_the_opcode_matches_the_configured_block_value() {
+        // PREVRANDAO, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, REVERT —
+        // surfaces the opcode's result as the revert's output bytes, the
+        // same way `standalone_revert_surfaces_the_revert_reason_bytes`
+        // surfaces a literal value.
+        let code = hex::decode("4460005260206000fd").unwrap();
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let target = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(target),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+        let randao = revm::primitives::B256::repeat_byte(0x7A);
+        let block = BlockEnv {
+            prevrandao: Some(randao),
+            ..Default::default()
+        };
+        let call = tx(0, 1_000_000, Some(target), vec![]);
+
+        let outcome = run_standalone(sender, &call, block, &RollupConfig::default(), &mut db)
+            .unwrap()
+            .outcome;
+
+        assert_eq!(outcome, TxOutcome::Reverted(randao.to_vec()));
+    }
+
+    #[test]
+    fn prevrandao_opcode_reads_difficulty_instead_under_a_pre_merge_spec() {
+        // Same opcode (0x44) as `prevrandao_read_by_the_opcode_matches_the_configured_block_value`,
+        // but revm reads `block.difficulty` instead of `block.prevrandao`
+        // for any spec before `SpecId::MERGE` — the same bytecode, two
+        // different fields, depending on `config.spec_id`.
+        let code = hex::decode("4460005260206000fd").unwrap();
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let target = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(target),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+        let block = BlockEnv {
+            difficulty: U256::from(123_456u64),
+            prevrandao: Some(revm::primitives::B256::repeat_byte(0x7A)),
+            ..Default::default()
+        };
+        let call = tx(0, 1_000_000, Some(target), vec![]);
+        let config = RollupConfig {
+            spec_id: SpecId::LONDON,
+            ..RollupConfig::default()
+        };
+
+        let outcome = run_standalone(sender, &call, block, &config, &mut db)
+            .unwrap()
+            .outcome;
+
+        assert_eq!(
+            outcome,
+            TxOutcome::Reverted(U256::from(123_456u64).to_be_bytes::<32>().to_vec())
+        );
+    }
+
+    #[test]
+    fn basefee_read_by_the_opcode_matches_the_configured_block_value() {
+        // BASEFEE, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, REVERT —
+        // surfaces the opcode's result as the revert's output bytes, the
+        // same way `prevrandao_read_by_the_opcode_matches_the_configured_block_value`
+        // surfaces `block.prevrandao`.
+        let code = hex::decode("4860005260206000fd").unwrap();
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let target = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(target),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+        let basefee = U256::from(5u64);
+        let block = BlockEnv {
+            basefee,
+            ..Default::default()
+        };
+        let call = tx(0, 1_000_000, Some(target), vec![]);
+        let config = RollupConfig {
+            spec_id: SpecId::LONDON,
+            ..RollupConfig::default()
+        };
+
+        let outcome = run_standalone(sender, &call, block, &config, &mut db)
+            .unwrap()
+            .outcome;
+
+        assert_eq!(
+            outcome,
+            TxOutcome::Reverted(basefee.to_be_bytes::<32>().to_vec())
+        );
+    }
+
+    #[test]
+    fn basefee_opcode_halts_as_not_activated_before_london() {
+        // BASEFEE, PUSH1 0x00, MSTORE, PUSH1 0x20, PUSH1 0x00, REVERT
+        let code = hex::decode("4860005260206000fd").unwrap();
+
+        let outcome = run_opcode_under_spec(&code, SpecId::BERLIN);
+
+        assert!(matches!(outcome, TxOutcome::Halted(Halt::NotActivated)));
+    }
+
+    #[test]
+    fn next_base_fee_rises_after_a_full_block() {
+        let next = next_base_fee(30_000_000, 30_000_000, U256::from(1_000_000_000u64));
+        assert!(next > U256::from(1_000_000_000u64));
+    }
+
+    #[test]
+    fn next_base_fee_falls_after_an_empty_block() {
+        let next = next_base_fee(0, 30_000_000, U256::from(1_000_000_000u64));
+        assert!(next < U256::from(1_000_000_000u64));
+    }
+
+    #[test]
+    fn next_base_fee_is_unchanged_exactly_at_the_gas_target() {
+        let next = next_base_fee(15_000_000, 30_000_000, U256::from(1_000_000_000u64));
+        assert_eq!(next, U256::from(1_000_000_000u64));
+    }
+
+    #[test]
+    fn next_base_fee_saturates_instead_of_overflowing_on_a_huge_parent_base_fee() {
+        let next = next_base_fee(30_000_000, 30_000_000, U256::MAX);
+        assert_eq!(next, U256::MAX);
+    }
+
+    #[test]
+    fn self_recursive_calls_terminate_with_a_bounded_log_instead_of_hitting_depth_1024() {
+        // A contract recursing via CALL runs out of gas (the 63/64 rule
+        // decays each level's stipend) long before it could ever reach
+        // revm's 1024 call-depth limit, so this only needs to confirm the
+        // call-depth concern doesn't translate into unbounded guest memory
+        // use: the merged log stays a handful of entries regardless of how
+        // many times the contract actually recursed.
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recursive = EvmAddress::repeat_byte(0xEE);
+        let mut db = funded_db(sender);
+        // PUSH1 0 x5, ADDRESS, GAS, CALL, STOP: calls itself forwarding all
+        // remaining gas, ignoring the result, until it can't afford another
+        // call.
+        let code = hex::decode("60006000600060006000305af100").unwrap();
+        db.insert_account_info(
+            address::to_revm(recursive),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+        let block = BlockEnv {
+            gas_limit: U256::from(30_000_000u64),
+            ..Default::default()
+        };
+        let call = tx(0, 30_000_000, Some(recursive), vec![]);
+
+        let receipt = run_standalone(
+            sender,
+            &call,
+            block.clone(),
+            &RollupConfig::default(),
+            &mut db,
+        )
+        .unwrap();
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, call)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(tree.includes, vec![true]);
+        assert!(
+            log.accounts.len() <= 5,
+            "expected a small, depth-independent log, got {} entries",
+            log.accounts.len()
+        );
+    }
+
+    #[test]
+    fn a_static_call_that_attempts_sstore_reverts_without_leaking_the_write_into_the_log() {
+        // EIP-214: SSTORE inside a STATICCALL's read-only context reverts
+        // that sub-call (not the whole transaction), and the attempted
+        // write never reaches state. `callee`'s code: PUSH1 1 PUSH1 0
+        // SSTORE STOP — would write slot 0 if it ever got to run for real.
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let caller = EvmAddress::repeat_byte(0xCC);
+        let callee = EvmAddress::repeat_byte(0xDD);
+        let mut db = funded_db(sender);
+
+        let callee_code = hex::decode("600160005500").unwrap();
+        db.insert_account_info(
+            address::to_revm(callee),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(callee_code.into())),
+                ..Default::default()
+            },
+        );
+
+        // PUSH1 0 x4 (retSize, retOffset, argsSize, argsOffset), PUSH20
+        // <callee>, GAS, STATICCALL, POP, STOP.
+        let mut caller_code = hex::decode("6000600060006000").unwrap();
+        caller_code.push(0x73);
+        caller_code.extend_from_slice(callee.as_bytes());
+        caller_code.extend_from_slice(&hex::decode("5afa5000").unwrap());
+        db.insert_account_info(
+            address::to_revm(caller),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(caller_code.into())),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let call = tx(0, 1_000_000, Some(caller), vec![]);
+
+        let receipt = run_standalone(
+            sender,
+            &call,
+            block.clone(),
+            &RollupConfig::default(),
+            &mut db,
+        )
+        .unwrap();
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+
+        let (_, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, call)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+        // The reverted sub-call never touches `callee` at all — its SSTORE
+        // was rejected before it could write anything — so `callee` either
+        // has no log entry, or (if it does) no storage write in it.
+        let leaked_write = log
+            .accounts
+            .iter()
+            .find(|entry| entry.address == callee)
+            .is_some_and(|entry| !entry.storage.is_empty());
+        assert!(
+            !leaked_write,
+            "expected no storage write to survive a reverted static-context SSTORE"
+        );
+    }
+
+    #[test]
+    fn budget_splits_a_four_transaction_bundle_into_two_sub_trees() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let txs: Vec<_> = (0..4)
+            .map(|n| {
+                (
+                    sender,
+                    tx(
+                        n,
+                        21_000,
+                        Some(EvmAddress::repeat_byte(0xBB + n as u8)),
+                        vec![],
+                    ),
+                )
+            })
+            .collect();
+        let block = BlockEnv {
+            gas_limit: U256::from(10_000_000u64),
+            ..Default::default()
+        };
+
+        let mut whole_db = funded_db(sender);
+        let (whole_tree, whole_log) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            block.clone(),
+            SpecId::LATEST,
+            false,
+            &mut whole_db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        let mut budgeted_db = funded_db(sender);
+        let trees =
+            apply_transactions_with_budget(&txs, block, SpecId::LATEST, &mut budgeted_db, 450);
+
+        assert_eq!(trees.len(), 2, "expected the budget to force a split");
+        assert_eq!(
+            trees.iter().map(|(t, _)| t.includes.len()).sum::<usize>(),
+            txs.len()
+        );
+
+        let merged_includes: Vec<bool> =
+            trees.iter().flat_map(|(t, _)| t.includes.clone()).collect();
+        assert_eq!(merged_includes, whole_tree.includes);
+
+        let merged_log = trees
+            .into_iter()
+            .map(|(_, log)| log)
+            .reduce(EvmStateLog::merge)
+            .unwrap();
+        assert_eq!(merged_log, whole_log);
+    }
+
+    #[test]
+    fn access_limit_trips_on_a_storage_heavy_transaction() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let contract = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        // Writes three separate storage slots, then stops.
+        let code = hex::decode("60016000556002600155600360025500").unwrap();
+        db.insert_account_info(
+            address::to_revm(contract),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let txs = vec![(sender, tx(0, 100_000, Some(contract), vec![]))];
+
+        let result =
+            apply_transactions_with_access_limit(&[], &txs, block, SpecId::LATEST, &mut db, 3);
+
+        assert_eq!(result, Err(AccessLimitError::ResourceExhausted { max: 3 }));
+    }
+
+    #[test]
+    fn access_limit_leaves_db_untouched_when_it_trips() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let contract = EvmAddress::repeat_byte(0xCC);
+        let mut db = funded_db(sender);
+        // Same storage-heavy contract as the test above: writes three
+        // separate storage slots, then stops.
+        let code = hex::decode("60016000556002600155600360025500").unwrap();
+        db.insert_account_info(
+            address::to_revm(contract),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+
+        let sender_before = db.basic(address::to_revm(sender)).unwrap();
+        let contract_before = db.basic(address::to_revm(contract)).unwrap();
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let txs = vec![(sender, tx(0, 100_000, Some(contract), vec![]))];
+
+        let result =
+            apply_transactions_with_access_limit(&[], &txs, block, SpecId::LATEST, &mut db, 3);
+        assert_eq!(result, Err(AccessLimitError::ResourceExhausted { max: 3 }));
+
+        // A caller retrying against the same `db` must not find any part of
+        // the tripped bundle already applied.
+        assert_eq!(db.basic(address::to_revm(sender)).unwrap(), sender_before);
+        assert_eq!(
+            db.basic(address::to_revm(contract)).unwrap(),
+            contract_before
+        );
+        assert_eq!(
+            db.storage(address::to_revm(contract), U256::from(0))
+                .unwrap(),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn rollup_config_controls_the_code_size_limit_and_the_chain_id_check() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        // Constructor (12 bytes) that CODECOPYs and returns the 21 bytes of
+        // runtime code (all STOP) appended after it, so the init code itself
+        // stays well under EIP-3860's limit even once that limit is derived
+        // from a tiny `max_code_size` below.
+        let init_code =
+            hex::decode("6015600c60003960156000f3000000000000000000000000000000000000000000")
+                .unwrap();
+        let deploy = tx(0, 200_000, None, init_code);
+
+        // Under the default limit (EIP-170's ~24KB), the deployment succeeds.
+        let mut db = funded_db(sender);
+        let receipt = run_standalone(
+            sender,
+            &deploy,
+            block.clone(),
+            &RollupConfig::default(),
+            &mut db,
+        )
+        .unwrap();
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+
+        // Shrinking `max_code_size` below the deployed code's 21-byte length
+        // halts the same deployment instead.
+        let mut db = funded_db(sender);
+        let tight_limit = RollupConfig {
+            max_code_size: 20,
+            ..RollupConfig::default()
+        };
+        let receipt =
+            run_standalone(sender, &deploy, block.clone(), &tight_limit, &mut db).unwrap();
+        assert_eq!(
+            receipt.outcome,
+            TxOutcome::Halted(revm::primitives::Halt::CreateContractSizeLimit)
+        );
+
+        // A transaction whose chain ID doesn't match the configured one is
+        // rejected by default...
+        let mismatched = tx_with_chain_id(0, 21_000, Some(EvmAddress::repeat_byte(0xBB)), 999);
+        let mut db = funded_db(sender);
+        let err = run_standalone(
+            sender,
+            &mismatched,
+            block.clone(),
+            &RollupConfig::default(),
+            &mut db,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            TxError::Evm(EVMError::Transaction(
+                revm::primitives::InvalidTransaction::InvalidChainId
+            ))
+        ));
+
+        // ...but accepted once `allow_pre_155` opts out of that check.
+        let mut db = funded_db(sender);
+        let lenient = RollupConfig {
+            allow_pre_155: true,
+            ..RollupConfig::default()
+        };
+        let receipt = run_standalone(sender, &mismatched, block, &lenient, &mut db).unwrap();
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+    }
+
+    fn sample_receipts() -> Vec<TxReceipt> {
+        vec![
+            TxReceipt {
+                gas_used: 21_000,
+                outcome: TxOutcome::Success,
+                effective_gas_price: PU256::from(100u64),
+            },
+            TxReceipt {
+                gas_used: 50_000,
+                outcome: TxOutcome::Reverted(vec![0xAB, 0xCD]),
+                effective_gas_price: PU256::from(50u64),
+            },
+        ]
+    }
+
+    #[test]
+    fn receipts_root_matches_a_merkle_tree_over_the_receipts_own_digests() {
+        let receipts = sample_receipts();
+
+        let expected_leaves: Vec<H256> = receipts
+            .iter()
+            .map(|receipt| {
+                let encoded = bincode::serialize(receipt).unwrap();
+                H256::from(Keccak256::digest(&encoded).as_ref())
+            })
+            .collect();
+        let expected = MerkleTree::commit(&expected_leaves);
+
+        assert_eq!(receipts_root(&receipts), expected);
+    }
+
+    #[test]
+    fn receipts_root_is_sensitive_to_order_and_content() {
+        let receipts = sample_receipts();
+        let mut reordered = receipts.clone();
+        reordered.reverse();
+
+        assert_ne!(receipts_root(&receipts), receipts_root(&reordered));
+
+        let mut changed = receipts.clone();
+        changed[0].gas_used += 1;
+        assert_ne!(receipts_root(&receipts), receipts_root(&changed));
+    }
+
+    #[test]
+    fn applying_a_valid_authorization_installs_the_delegation_designator_and_bumps_the_nonce() {
+        use crate::tx::{sign_authorization, SetCodeAuthorization};
+
+        let authority = EvmAddress::repeat_byte(0xAA);
+        let delegate = EvmAddress::repeat_byte(0xDD);
+        let mut db = funded_db(authority);
+
+        let auth = SetCodeAuthorization {
+            chain_id: 1,
+            address: delegate,
+            nonce: 0,
+            authority,
+            mac: sign_authorization(1, delegate, 0, authority),
+        };
+
+        apply_set_code_authorizations(&[auth], &mut db).unwrap();
+
+        let info = db.basic(address::to_revm(authority)).unwrap().unwrap();
+        assert_eq!(info.nonce, 1);
+        let mut expected_code = DELEGATION_DESIGNATOR_PREFIX.to_vec();
+        expected_code.extend_from_slice(delegate.as_bytes());
+        assert_eq!(
+            info.code.unwrap().original_bytes().as_ref(),
+            expected_code.as_slice()
+        );
+    }
+
+    #[test]
+    fn an_authorization_with_a_forged_authority_is_skipped_rather_than_applied() {
+        use crate::tx::{sign_authorization, SetCodeAuthorization};
+
+        let authority = EvmAddress::repeat_byte(0xAA);
+        let forged_authority = EvmAddress::repeat_byte(0xBB);
+        let delegate = EvmAddress::repeat_byte(0xDD);
+        let mut db = funded_db(authority);
+
+        // Signed for `authority`, but claims `forged_authority` instead.
+        let auth = SetCodeAuthorization {
+            chain_id: 1,
+            address: delegate,
+            nonce: 0,
+            authority: forged_authority,
+            mac: sign_authorization(1, delegate, 0, authority),
+        };
+
+        apply_set_code_authorizations(&[auth], &mut db).unwrap();
+
+        assert_eq!(db.basic(address::to_revm(forged_authority)).unwrap(), None);
+    }
+
+    /// Wraps a `Database`, counting every `code_by_hash` call — so a test
+    /// can assert outright that a plain ETH transfer to an EOA recipient
+    /// never fetches code, rather than just assuming revm skips it.
+    struct CodeFetchCountingDb<DB> {
+        inner: DB,
+        code_by_hash_calls: usize,
+    }
+
+    impl<DB: Database> Database for CodeFetchCountingDb<DB> {
+        type Error = DB::Error;
+
+        fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.inner.basic(address)
+        }
+
+        fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.code_by_hash_calls += 1;
+            self.inner.code_by_hash(code_hash)
+        }
+
+        fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.inner.storage(address, index)
+        }
+
+        fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+            self.inner.block_hash(number)
+        }
+    }
+
+    impl<DB: DatabaseCommit> DatabaseCommit for CodeFetchCountingDb<DB> {
+        fn commit(&mut self, changes: RevmHashMap<Address, Account>) {
+            self.inner.commit(changes)
+        }
+    }
+
+    /// A funded sender plus, if given, an existing recipient with the given
+    /// balance — otherwise the recipient is left absent, so the transfer
+    /// itself brings it into existence.
+    fn transfer_db(
+        sender: EvmAddress,
+        existing_recipient: Option<(EvmAddress, U256)>,
+    ) -> CodeFetchCountingDb<InMemoryDB> {
+        let mut inner = funded_db(sender);
+        if let Some((recipient, balance)) = existing_recipient {
+            inner.insert_account_info(
+                address::to_revm(recipient),
+                AccountInfo {
+                    balance,
+                    ..Default::default()
+                },
+            );
+        }
+        CodeFetchCountingDb {
+            inner,
+            code_by_hash_calls: 0,
+        }
+    }
+
+    #[test]
+    fn standalone_transfer_to_an_existing_eoa_charges_exactly_21000_gas_without_fetching_code() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = transfer_db(sender, Some((recipient, U256::from(500u64))));
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let transfer = tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64));
+        let receipt =
+            run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+        assert_eq!(receipt.gas_used, 21_000);
+        assert_eq!(db.code_by_hash_calls, 0);
+    }
+
+    #[test]
+    fn standalone_transfer_to_an_absent_address_creates_it_without_fetching_code() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let fresh = EvmAddress::repeat_byte(0xCC);
+        let mut db = transfer_db(sender, None);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let transfer = tx_with_value(0, 21_000, Some(fresh), PU256::from(1_000u64));
+        let receipt =
+            run_standalone(sender, &transfer, block, &RollupConfig::default(), &mut db).unwrap();
+
+        assert_eq!(receipt.outcome, TxOutcome::Success);
+        assert_eq!(receipt.gas_used, 21_000);
+        assert_eq!(db.code_by_hash_calls, 0);
+    }
+
+    /// [`run_standalone`] never commits, so the exact balance and nonce
+    /// changes a transfer produces are checked the same way every other
+    /// such assertion in this module is: via [`apply_transactions`], which
+    /// drives a transaction through the identical per-tx execution path
+    /// (`add_to_env`, `configure_from_rollup`, `EVM::transact`) before
+    /// folding the result into a log.
+    #[test]
+    fn transfer_to_an_existing_eoa_moves_exactly_its_value_and_the_sender_pays_gas() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = transfer_db(sender, Some((recipient, U256::from(500u64))));
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let transfer = tx_with_value(0, 21_000, Some(recipient), PU256::from(1_000u64));
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, transfer)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        assert_eq!(db.code_by_hash_calls, 0);
+
+        let sender_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == sender)
+            .expect("sender should be journaled");
+        let Access::Write(Some(sender_info)) = &sender_entry.info else {
+            panic!("expected an info write for the sender");
+        };
+        // With `BlockEnv::default`'s zero basefee, the effective gas price
+        // is exactly the transaction's priority fee of 1.
+        assert_eq!(
+            sender_info.balance,
+            U256::from(1_000_000_000_000u64) - U256::from(1_000u64) - U256::from(21_000u64)
+        );
+        assert_eq!(sender_info.nonce, 1);
+        assert!(sender_entry.code.is_none());
+
+        let recipient_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == recipient)
+            .expect("recipient should be journaled");
+        let Access::Write(Some(recipient_info)) = &recipient_entry.info else {
+            panic!("expected an info write for the recipient");
+        };
+        assert_eq!(recipient_info.balance, U256::from(1_500u64));
+        assert!(!recipient_entry.created);
+        assert!(recipient_entry.code.is_none());
+    }
+
+    #[test]
+    fn transfer_to_an_absent_address_creates_it_with_exactly_the_transferred_balance() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let fresh = EvmAddress::repeat_byte(0xCC);
+        let mut db = transfer_db(sender, None);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let transfer = tx_with_value(0, 21_000, Some(fresh), PU256::from(1_000u64));
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, transfer)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        assert_eq!(tree.includes, vec![true]);
+        assert_eq!(db.code_by_hash_calls, 0);
+
+        let fresh_entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == fresh)
+            .expect("the newly created account should be journaled");
+        let Access::Write(Some(fresh_info)) = &fresh_entry.info else {
+            panic!("expected an info write for the newly created account");
+        };
+        assert_eq!(fresh_info.balance, U256::from(1_000u64));
+        // `created` tracks contract creation (revm's `Created` account
+        // status), not merely coming into existence — a plain transfer to a
+        // previously-absent EOA doesn't set it.
+        assert!(!fresh_entry.created);
+        assert!(fresh_entry.code.is_none());
+    }
+}
@@ -0,0 +1,97 @@
+//! Seeding a rollup's initial state from a genesis allocation.
+//!
+//! A chain's genesis fixes the balances, nonces, code, and storage every
+//! node starts from, before any block has been applied.
+//!
+//! This crate has no Merkle-Patricia trie implementation yet, so
+//! [`apply_genesis`] produces a flat commitment (the same Keccak256-over-
+//! bincode scheme as [`crate::log::EvmStateLog::commitment`]) rather than a
+//! real state root. It's a placeholder `prev` state commitment for the
+//! first block, to be swapped for a trie root once a trie module exists.
+
+use std::collections::BTreeMap;
+
+use primitive_types::{H256, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::address::EvmAddress;
+
+/// One account's starting balance, nonce, code, and storage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GenesisAccount {
+    pub balance: U256,
+    pub nonce: u64,
+    pub code: Vec<u8>,
+    pub storage: BTreeMap<H256, U256>,
+}
+
+/// A rollup's genesis allocation, keyed by address.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Genesis {
+    pub alloc: BTreeMap<EvmAddress, GenesisAccount>,
+}
+
+/// Computes a deterministic commitment to `genesis`'s allocation: the
+/// Keccak256 hash of its canonical (bincode) serialization. `alloc` being a
+/// `BTreeMap` makes the serialized order (and so the commitment) independent
+/// of the order accounts were inserted in.
+///
+/// A genesis with no allocation is a special case: rather than hashing the
+/// bincode encoding of an empty map, this returns
+/// [`crate::trie::EMPTY_ROOT`] — the root any real Merkle-Patricia trie
+/// would have with no entries, which is what this placeholder commitment
+/// should degenerate to when there's nothing to commit to.
+pub fn apply_genesis(genesis: &Genesis) -> H256 {
+    if genesis.alloc.is_empty() {
+        return crate::trie::EMPTY_ROOT;
+    }
+    let encoded = bincode::serialize(genesis).expect("Genesis is always serializable");
+    H256::from(Keccak256::digest(&encoded).as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(byte: u8) -> EvmAddress {
+        EvmAddress::repeat_byte(byte)
+    }
+
+    fn two_account_genesis() -> Genesis {
+        let mut alloc = BTreeMap::new();
+        alloc.insert(
+            addr(1),
+            GenesisAccount {
+                balance: U256::from(1_000u64),
+                nonce: 0,
+                code: vec![],
+                storage: BTreeMap::new(),
+            },
+        );
+        alloc.insert(
+            addr(2),
+            GenesisAccount {
+                balance: U256::zero(),
+                nonce: 1,
+                code: vec![0xfe],
+                storage: BTreeMap::new(),
+            },
+        );
+        Genesis { alloc }
+    }
+
+    #[test]
+    fn apply_genesis_is_deterministic() {
+        let genesis = two_account_genesis();
+        assert_eq!(apply_genesis(&genesis), apply_genesis(&genesis));
+
+        let mut different = two_account_genesis();
+        different.alloc.get_mut(&addr(2)).unwrap().nonce = 2;
+        assert_ne!(apply_genesis(&genesis), apply_genesis(&different));
+    }
+
+    #[test]
+    fn apply_genesis_of_an_empty_allocation_is_the_empty_trie_root() {
+        assert_eq!(apply_genesis(&Genesis::default()), crate::trie::EMPTY_ROOT);
+    }
+}
@@ -0,0 +1,121 @@
+//! Estimating how much a block's state transition grew or shrank the trie,
+//! for operator capacity planning.
+//!
+//! Host-only: a guest just applies a log (see [`crate::log::apply_rw_log`])
+//! and doesn't care how many leaves that added or removed.
+
+use primitive_types::H256;
+
+use crate::log::{Access, EvmStateLog};
+
+/// The net change in trie leaves [`state_size_delta`] attributes to a
+/// block's merged log: accounts and storage slots added versus removed.
+///
+/// `pre_root`/`post_root` are carried through for the caller's own
+/// bookkeeping (which block transition this delta belongs to) — this
+/// crate has no real Merkle-Patricia trie (see [`crate::trie`]) to check
+/// leaf existence against them, so the counts themselves come entirely
+/// from the log, the same way [`EvmStateLog::account_transitions`] already
+/// derives account creation and destruction from the log's own
+/// bookkeeping rather than from a trie diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateSizeDelta {
+    pub pre_root: H256,
+    pub post_root: H256,
+    /// Accounts [`EvmStateLog::account_transitions`] reports as created.
+    pub new_accounts: usize,
+    /// Accounts [`EvmStateLog::account_transitions`] reports as destroyed.
+    pub deleted_accounts: usize,
+    /// Storage slots with a final access of `Write(Some(_))`.
+    pub new_storage_slots: usize,
+    /// Storage slots with a final access of `Write(None)`.
+    pub deleted_storage_slots: usize,
+}
+
+/// Reports [`StateSizeDelta`] for `log`, the merged read/write log of a
+/// block that moved state from `pre_root` to `post_root`.
+///
+/// Reuses [`EvmStateLog::account_transitions`] for the account counts;
+/// storage slots are counted the same way inline, since there's no
+/// existing helper that just counts them without building the leaf lists
+/// [`EvmStateLog::update_storage_roots`] needs.
+pub fn state_size_delta(pre_root: H256, post_root: H256, log: &EvmStateLog) -> StateSizeDelta {
+    let (created, destroyed) = log.account_transitions();
+
+    let mut new_storage_slots = 0;
+    let mut deleted_storage_slots = 0;
+    for entry in &log.accounts {
+        for (_, access) in &entry.storage {
+            match access {
+                Access::Write(Some(_)) => new_storage_slots += 1,
+                Access::Write(None) => deleted_storage_slots += 1,
+                Access::Read(_) => {}
+            }
+        }
+    }
+
+    StateSizeDelta {
+        pre_root,
+        post_root,
+        new_accounts: created.len(),
+        deleted_accounts: destroyed.len(),
+        new_storage_slots,
+        deleted_storage_slots,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::EvmAddress;
+    use crate::log::AccountLogEntry;
+    use primitive_types::U256;
+    use revm::primitives::AccountInfo;
+
+    fn addr(n: u8) -> EvmAddress {
+        EvmAddress::repeat_byte(n)
+    }
+
+    #[test]
+    fn reports_deltas_for_a_block_that_creates_one_account_and_deletes_another() {
+        let created_account = AccountLogEntry {
+            address: addr(1),
+            info: Access::Write(Some(AccountInfo::default())),
+            code: None,
+            storage: vec![
+                (H256::repeat_byte(0xA1), Access::Write(Some(U256::from(1)))),
+                (H256::repeat_byte(0xA2), Access::Write(Some(U256::from(2)))),
+            ],
+            storage_root: None,
+            created: true,
+        };
+        let destroyed_account = AccountLogEntry {
+            address: addr(2),
+            info: Access::Write(None),
+            code: None,
+            storage: vec![],
+            storage_root: None,
+            created: false,
+        };
+        let log = EvmStateLog {
+            accounts: vec![created_account, destroyed_account],
+            sequencer_balances: vec![],
+        };
+
+        let pre_root = H256::repeat_byte(0x11);
+        let post_root = H256::repeat_byte(0x22);
+        let delta = state_size_delta(pre_root, post_root, &log);
+
+        assert_eq!(
+            delta,
+            StateSizeDelta {
+                pre_root,
+                post_root,
+                new_accounts: 1,
+                deleted_accounts: 1,
+                new_storage_slots: 2,
+                deleted_storage_slots: 0,
+            }
+        );
+    }
+}
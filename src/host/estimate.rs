@@ -0,0 +1,197 @@
+//! Binary-searching a transaction's minimal gas limit against a
+//! [`StateStore`], the way `eth_estimateGas` does — for a sequencer wanting
+//! to know how much gas to offer a transaction before including it in a
+//! bundle.
+//!
+//! Host-only: a guest replaying an already-built block has no use for this,
+//! it's purely pre-inclusion tooling for whoever assembles one.
+
+use revm::primitives::BlockEnv;
+use thiserror::Error;
+
+use crate::address::EvmAddress;
+use crate::config::RollupConfig;
+use crate::evm::{run_standalone, TxError, TxOutcome};
+use crate::host::store::{StateStore, WitnessDB, WitnessDBError};
+use crate::tx::EvmTransaction;
+
+/// The EVM-wide floor on a transaction's gas cost, below which nothing can
+/// ever succeed — the known-failing end of [`estimate_gas`]'s search range.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+/// Errors raised while estimating a transaction's gas via [`estimate_gas`].
+#[derive(Debug, Error)]
+pub enum EstimateGasError<E> {
+    /// `tx` reverted or halted even at the highest gas limit probed (the
+    /// block's own gas limit) — no gas limit makes it succeed, so there's
+    /// no estimate to report.
+    #[error("transaction cannot succeed within the block gas limit")]
+    AlwaysFails,
+    /// Running a candidate gas limit failed for a reason unrelated to the
+    /// search itself: a store lookup errored, or revm rejected the
+    /// transaction outright.
+    #[error(transparent)]
+    Tx(#[from] TxError<WitnessDBError<E>>),
+}
+
+/// `tx` with its own declared gas limit overridden to `gas_limit`, for
+/// probing a candidate without mutating the caller's transaction.
+fn with_gas_limit(tx: &EvmTransaction, gas_limit: u64) -> EvmTransaction {
+    let mut tx = tx.clone();
+    match &mut tx {
+        EvmTransaction::Legacy(t) => t.common.gas_limit = gas_limit,
+        EvmTransaction::Eip1559(t) => t.common.gas_limit = gas_limit,
+        EvmTransaction::SetCode(t) => t.common.gas_limit = gas_limit,
+    }
+    tx
+}
+
+/// Runs `tx` at `gas_limit` against a fresh [`WitnessDB`] over `store`,
+/// reporting whether it succeeded.
+fn succeeds_with<S: StateStore>(
+    caller: EvmAddress,
+    tx: &EvmTransaction,
+    gas_limit: u64,
+    block: &BlockEnv,
+    config: &RollupConfig,
+    store: &S,
+) -> Result<bool, TxError<WitnessDBError<S::Error>>> {
+    let candidate = with_gas_limit(tx, gas_limit);
+    let mut db = WitnessDB::new(store);
+    let receipt = run_standalone(caller, &candidate, block.clone(), config, &mut db)?;
+    Ok(receipt.outcome == TxOutcome::Success)
+}
+
+/// Binary-searches the smallest gas limit `tx` (run as `caller`) succeeds
+/// with against `store`, over the range from [`MIN_TRANSACTION_GAS`] (below
+/// which nothing can ever succeed) up to `block.gas_limit`. Each candidate
+/// runs against its own fresh [`WitnessDB`] wrapping `store`, so no probe's
+/// reads leak into the next.
+///
+/// If `tx` still reverts or halts at `block.gas_limit` — the highest limit
+/// it could ever be offered — this reports
+/// [`EstimateGasError::AlwaysFails`] rather than a number nobody could
+/// actually use.
+pub fn estimate_gas<S: StateStore>(
+    caller: EvmAddress,
+    tx: &EvmTransaction,
+    block: &BlockEnv,
+    config: &RollupConfig,
+    store: &S,
+) -> Result<u64, EstimateGasError<S::Error>> {
+    let max_gas = u64::try_from(block.gas_limit).unwrap_or(u64::MAX);
+
+    if !succeeds_with(caller, tx, max_gas, block, config, store)? {
+        return Err(EstimateGasError::AlwaysFails);
+    }
+
+    let mut lo = MIN_TRANSACTION_GAS.saturating_sub(1);
+    let mut hi = max_gas;
+    while lo + 1 < hi {
+        let mid = lo + (hi - lo) / 2;
+        if succeeds_with(caller, tx, mid, block, config, store)? {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    Ok(hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::store::InMemoryStateStore;
+    use crate::tx::{Eip1559Tx, TxCommon};
+    use primitive_types::{H256, U256 as PU256};
+    use revm::primitives::{AccountInfo, Bytecode, B256, U256};
+    use sha3::{Digest, Keccak256};
+
+    fn funded_store(sender: EvmAddress) -> InMemoryStateStore {
+        let mut store = InMemoryStateStore::default();
+        store.accounts.insert(
+            sender,
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        store
+    }
+
+    fn call_tx(to: EvmAddress, value: PU256, data: Vec<u8>) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 1_000_000,
+                to: Some(to),
+                value,
+                data,
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    #[test]
+    fn estimates_a_simple_transfer_at_exactly_its_intrinsic_gas() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let store = funded_store(sender);
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let estimate = estimate_gas(
+            sender,
+            &call_tx(recipient, PU256::from(1_000u64), vec![]),
+            &block,
+            &RollupConfig::default(),
+            &store,
+        )
+        .unwrap();
+
+        assert_eq!(estimate, MIN_TRANSACTION_GAS);
+    }
+
+    #[test]
+    fn a_call_that_always_reverts_reports_an_estimation_error_instead_of_a_number() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut store = funded_store(sender);
+
+        // PUSH1 0x00 PUSH1 0x00 REVERT: reverts unconditionally, regardless
+        // of how much gas it's given.
+        let code = hex::decode("60006000fd").unwrap();
+        let code_hash = B256::from(H256::from(Keccak256::digest(&code).as_ref()).0);
+        let contract = EvmAddress::repeat_byte(0xCC);
+        store.accounts.insert(
+            contract,
+            AccountInfo {
+                code_hash,
+                code: Some(Bytecode::new_raw(code.clone().into())),
+                ..Default::default()
+            },
+        );
+        store.code.insert(code_hash, code);
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+
+        let err = estimate_gas(
+            sender,
+            &call_tx(contract, PU256::zero(), vec![]),
+            &block,
+            &RollupConfig::default(),
+            &store,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, EstimateGasError::AlwaysFails));
+    }
+}
@@ -0,0 +1,799 @@
+//! Host-side support: turning real chain state into a witness the guest can
+//! replay deterministically.
+
+#[cfg(feature = "host")]
+pub mod capacity;
+#[cfg(feature = "host")]
+pub mod estimate;
+pub mod nonce;
+#[cfg(feature = "host")]
+pub mod parallel;
+pub mod proof;
+pub mod rpc;
+pub mod store;
+pub mod sync;
+
+use std::collections::VecDeque;
+
+use primitive_types::{H256, U256 as PU256};
+use revm::primitives::{AccountInfo, Bytecode, B256};
+use revm::Database;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+use crate::address::{self, EvmAddress};
+use crate::trie::MerkleProof;
+
+/// One piece of chain state captured into a witness, in the exact order
+/// [`HostDB`] will read it back during replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessEntry {
+    Account(EvmAddress, Option<AccountInfo>),
+    Code(B256, Vec<u8>),
+    Storage(EvmAddress, PU256, PU256),
+    BlockHash(PU256, B256),
+    /// Like [`WitnessEntry::Code`], but for [`HostDB::with_code_registry`]:
+    /// the code's bytes, plus a proof that `keccak256(code)` is included in
+    /// the configured registry root — read back in place of a `Code` entry
+    /// rather than alongside it, so a witness is built for one mode or the
+    /// other, not both.
+    CodeProof(Vec<u8>, MerkleProof),
+}
+
+const ACCOUNT_TAG: u8 = 0;
+const CODE_TAG: u8 = 1;
+const STORAGE_TAG: u8 = 2;
+const BLOCK_HASH_TAG: u8 = 3;
+const CODE_PROOF_TAG: u8 = 4;
+
+/// Errors raised while decoding a [`WitnessEntry`] via
+/// [`WitnessEntry::decode`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WitnessEntryCodecError {
+    /// The bytes ended before a fixed-width field or a length-prefixed
+    /// payload did.
+    #[error("truncated witness entry: expected {expected} more bytes, found {found}")]
+    Truncated { expected: usize, found: usize },
+    /// The leading tag byte didn't match any known `WitnessEntry` variant.
+    #[error("unknown witness entry tag {0}")]
+    UnknownTag(u8),
+    /// An account's info didn't decode as valid bincode.
+    #[error("malformed witness entry payload: {0}")]
+    Malformed(String),
+}
+
+/// Splits off the first `n` bytes of `bytes`, or reports how many more were
+/// needed.
+fn take(bytes: &[u8], n: usize) -> Result<(&[u8], &[u8]), WitnessEntryCodecError> {
+    if bytes.len() < n {
+        return Err(WitnessEntryCodecError::Truncated {
+            expected: n,
+            found: bytes.len(),
+        });
+    }
+    Ok((&bytes[..n], &bytes[n..]))
+}
+
+/// Splits off a 4-byte little-endian length prefix followed by that many
+/// bytes, the same framing [`crate::bundle`] uses for its transaction
+/// frames.
+fn take_len_prefixed(bytes: &[u8]) -> Result<(&[u8], &[u8]), WitnessEntryCodecError> {
+    let (len_bytes, rest) = take(bytes, 4)?;
+    let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+    take(rest, len)
+}
+
+impl WitnessEntry {
+    /// Encodes this entry as a tag byte identifying its variant, followed
+    /// by its fields — fixed-width where a field has one (an address, a
+    /// hash, or a `U256`, always at its full raw byte width), or
+    /// length-prefixed, the same way [`crate::bundle`] frames a
+    /// transaction, where it doesn't (an account's info, or a code entry's
+    /// bytes).
+    ///
+    /// An account's info is bincode-serialized whole, through `serde` on
+    /// [`AccountInfo`] with `code` included, so those bytes follow whatever
+    /// shape revm gives that type.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            WitnessEntry::Account(address, info) => {
+                out.push(ACCOUNT_TAG);
+                out.extend_from_slice(address.as_bytes());
+                match info {
+                    Some(info) => {
+                        out.push(1);
+                        let encoded =
+                            bincode::serialize(info).expect("AccountInfo is always serializable");
+                        out.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+                        out.extend_from_slice(&encoded);
+                    }
+                    None => out.push(0),
+                }
+            }
+            WitnessEntry::Code(hash, code) => {
+                out.push(CODE_TAG);
+                out.extend_from_slice(hash.0.as_slice());
+                out.extend_from_slice(&(code.len() as u32).to_le_bytes());
+                out.extend_from_slice(code);
+            }
+            WitnessEntry::Storage(address, index, value) => {
+                out.push(STORAGE_TAG);
+                out.extend_from_slice(address.as_bytes());
+                let mut index_bytes = [0u8; 32];
+                index.to_big_endian(&mut index_bytes);
+                out.extend_from_slice(&index_bytes);
+                let mut value_bytes = [0u8; 32];
+                value.to_big_endian(&mut value_bytes);
+                out.extend_from_slice(&value_bytes);
+            }
+            WitnessEntry::BlockHash(number, hash) => {
+                out.push(BLOCK_HASH_TAG);
+                let mut number_bytes = [0u8; 32];
+                number.to_big_endian(&mut number_bytes);
+                out.extend_from_slice(&number_bytes);
+                out.extend_from_slice(hash.0.as_slice());
+            }
+            WitnessEntry::CodeProof(code, proof) => {
+                out.push(CODE_PROOF_TAG);
+                out.extend_from_slice(&(code.len() as u32).to_le_bytes());
+                out.extend_from_slice(code);
+                out.extend_from_slice(&(proof.leaf_index as u64).to_le_bytes());
+                out.extend_from_slice(&(proof.siblings.len() as u32).to_le_bytes());
+                for sibling in &proof.siblings {
+                    out.extend_from_slice(sibling.as_bytes());
+                }
+            }
+        }
+        out
+    }
+
+    /// Decodes a single entry encoded by [`WitnessEntry::encode`]. `bytes`
+    /// must hold exactly one encoded entry — trailing bytes aren't
+    /// reported as an error, since a caller framing a stream of entries
+    /// (e.g. with its own length prefixes) already knows where one ends.
+    pub fn decode(bytes: &[u8]) -> Result<WitnessEntry, WitnessEntryCodecError> {
+        let (tag, rest) = take(bytes, 1)?;
+        match tag[0] {
+            ACCOUNT_TAG => {
+                let (address, rest) = take(rest, 20)?;
+                let (has_info, rest) = take(rest, 1)?;
+                let info = match has_info[0] {
+                    0 => None,
+                    _ => {
+                        let (payload, _) = take_len_prefixed(rest)?;
+                        Some(
+                            bincode::deserialize(payload)
+                                .map_err(|e| WitnessEntryCodecError::Malformed(e.to_string()))?,
+                        )
+                    }
+                };
+                Ok(WitnessEntry::Account(EvmAddress::from_slice(address), info))
+            }
+            CODE_TAG => {
+                let (hash, rest) = take(rest, 32)?;
+                let (payload, _) = take_len_prefixed(rest)?;
+                Ok(WitnessEntry::Code(B256::from_slice(hash), payload.to_vec()))
+            }
+            STORAGE_TAG => {
+                let (address, rest) = take(rest, 20)?;
+                let (index, rest) = take(rest, 32)?;
+                let (value, _) = take(rest, 32)?;
+                Ok(WitnessEntry::Storage(
+                    EvmAddress::from_slice(address),
+                    PU256::from_big_endian(index),
+                    PU256::from_big_endian(value),
+                ))
+            }
+            BLOCK_HASH_TAG => {
+                let (number, rest) = take(rest, 32)?;
+                let (hash, _) = take(rest, 32)?;
+                Ok(WitnessEntry::BlockHash(
+                    PU256::from_big_endian(number),
+                    B256::from_slice(hash),
+                ))
+            }
+            CODE_PROOF_TAG => {
+                let (code, rest) = take_len_prefixed(rest)?;
+                let (leaf_index, rest) = take(rest, 8)?;
+                let leaf_index = u64::from_le_bytes(leaf_index.try_into().unwrap()) as usize;
+                let (sibling_count, rest) = take(rest, 4)?;
+                let sibling_count = u32::from_le_bytes(sibling_count.try_into().unwrap()) as usize;
+                let mut siblings = Vec::with_capacity(sibling_count);
+                let mut rest = rest;
+                for _ in 0..sibling_count {
+                    let (sibling, remainder) = take(rest, 32)?;
+                    siblings.push(H256::from_slice(sibling));
+                    rest = remainder;
+                }
+                Ok(WitnessEntry::CodeProof(
+                    code.to_vec(),
+                    MerkleProof {
+                        leaf_index,
+                        siblings,
+                    },
+                ))
+            }
+            other => Err(WitnessEntryCodecError::UnknownTag(other)),
+        }
+    }
+}
+
+/// Opt-in config for reading `BLOCKHASH` beyond revm's native 256-block
+/// window from an on-chain ring buffer instead, for rollups that expose
+/// one — e.g. a contract in the style of
+/// [EIP-2935](https://eips.ethereum.org/EIPS/eip-2935)'s history storage.
+///
+/// With this configured, [`HostDB::block_hash`] and
+/// [`crate::host::store::WitnessDB::block_hash`] read slot
+/// `number % window` of `address`'s storage instead of consulting a
+/// dedicated [`WitnessEntry::BlockHash`] — so the read is journaled (and
+/// later verified) as an ordinary [`WitnessEntry::Storage`] access, provable
+/// against the state root like any other slot, rather than resting on a
+/// witness kind of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHashHistory {
+    pub address: EvmAddress,
+    pub window: u64,
+}
+
+impl BlockHashHistory {
+    fn slot(&self, number: u64) -> PU256 {
+        PU256::from(number % self.window)
+    }
+}
+
+/// Errors raised while replaying a witness through [`HostDB`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HostDBError {
+    /// The witness ran out of entries before the guest stopped reading.
+    #[error("witness exhausted")]
+    Exhausted,
+    /// The next witness entry didn't match the kind or key the guest asked
+    /// for. This means the witness was built for a different execution.
+    #[error("witness entry out of order: expected {expected}")]
+    OutOfOrder { expected: &'static str },
+    /// A [`WitnessEntry::CodeProof`] either carried code whose hash didn't
+    /// match the one requested, or a proof that didn't verify against the
+    /// configured code registry root.
+    #[error("code proof for {0} did not verify against the code registry root")]
+    InvalidCodeProof(B256),
+    /// A [`WitnessEntry::Code`] entry claimed to be the code for `code_hash`,
+    /// but its actual bytes don't hash to it — the host supplied code that
+    /// doesn't match what the guest asked for.
+    #[error("code supplied for {0} does not hash to it")]
+    CodeHashMismatch(B256),
+}
+
+/// A [`Database`] that replays a pre-recorded witness instead of touching
+/// live chain state. Entries must be consumed in the exact order they were
+/// recorded.
+#[derive(Debug, Default)]
+pub struct HostDB {
+    entries: VecDeque<WitnessEntry>,
+    block_hash_history: Option<BlockHashHistory>,
+    code_registry_root: Option<H256>,
+}
+
+impl HostDB {
+    /// Builds a `HostDB` that will replay `entries` in order.
+    pub fn new(entries: Vec<WitnessEntry>) -> Self {
+        Self {
+            entries: entries.into(),
+            block_hash_history: None,
+            code_registry_root: None,
+        }
+    }
+
+    /// Opts this `HostDB` into reading `BLOCKHASH` from `history`'s ring
+    /// buffer instead of from dedicated [`WitnessEntry::BlockHash`] entries.
+    pub fn with_block_hash_history(mut self, history: BlockHashHistory) -> Self {
+        self.block_hash_history = Some(history);
+        self
+    }
+
+    /// Opts this `HostDB` into reading code from a content-addressed code
+    /// registry committed to by `root`: [`HostDB::code_by_hash`] will expect
+    /// [`WitnessEntry::CodeProof`] entries and verify each one's code against
+    /// `root` via [`MerkleProof::verify`], rather than trusting a
+    /// [`WitnessEntry::Code`] entry's claimed hash. This trades a hash
+    /// comparison for a proof verification — useful for a design that
+    /// already commits to deployed code in a state subtree and wants
+    /// `code_by_hash` reads to be provable against it.
+    pub fn with_code_registry(mut self, root: H256) -> Self {
+        self.code_registry_root = Some(root);
+        self
+    }
+}
+
+impl Database for HostDB {
+    type Error = HostDBError;
+
+    /// Reconstructs a code-less `AccountInfo` from the witness: balance,
+    /// nonce, and `code_hash` are trusted as given, but `code` is always
+    /// reset to `None` regardless of what the entry carried, so revm falls
+    /// through to [`HostDB::code_by_hash`] (verified against the actual
+    /// bytes) for anything but an empty-code account, rather than ever
+    /// trusting code smuggled in alongside the rest of an account's info.
+    /// An empty-code account (`code_hash == KECCAK_EMPTY`, e.g. an EOA)
+    /// never reaches `code_by_hash` at all — revm fills in
+    /// [`Bytecode::new`](revm::primitives::Bytecode::new) for that hash
+    /// itself, without a witness entry to ask for.
+    fn basic(
+        &mut self,
+        address: revm::primitives::Address,
+    ) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.entries.pop_front() {
+            Some(WitnessEntry::Account(addr, info)) if addr == address::from_revm(address) => {
+                Ok(info.map(|info| AccountInfo { code: None, ..info }))
+            }
+            Some(_) => Err(HostDBError::OutOfOrder {
+                expected: "account",
+            }),
+            None => Err(HostDBError::Exhausted),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(root) = self.code_registry_root {
+            return match self.entries.pop_front() {
+                Some(WitnessEntry::CodeProof(code, proof)) => {
+                    let digest = H256::from(Keccak256::digest(&code).as_ref());
+                    if digest.as_bytes() != code_hash.as_slice() || !proof.verify(root, digest) {
+                        return Err(HostDBError::InvalidCodeProof(code_hash));
+                    }
+                    Ok(Bytecode::new_raw(code.into()))
+                }
+                Some(_) => Err(HostDBError::OutOfOrder {
+                    expected: "code proof",
+                }),
+                None => Err(HostDBError::Exhausted),
+            };
+        }
+
+        match self.entries.pop_front() {
+            Some(WitnessEntry::Code(hash, code)) if hash == code_hash => {
+                let digest = B256::from(Keccak256::digest(&code).as_ref());
+                if digest != code_hash {
+                    return Err(HostDBError::CodeHashMismatch(code_hash));
+                }
+                Ok(Bytecode::new_raw(code.into()))
+            }
+            Some(_) => Err(HostDBError::OutOfOrder { expected: "code" }),
+            None => Err(HostDBError::Exhausted),
+        }
+    }
+
+    fn storage(
+        &mut self,
+        address: revm::primitives::Address,
+        index: revm::primitives::U256,
+    ) -> Result<revm::primitives::U256, Self::Error> {
+        let index = PU256(index.into_limbs());
+        match self.entries.pop_front() {
+            Some(WitnessEntry::Storage(addr, idx, value))
+                if addr == address::from_revm(address) && idx == index =>
+            {
+                Ok(revm::primitives::U256::from_limbs(value.0))
+            }
+            Some(_) => Err(HostDBError::OutOfOrder {
+                expected: "storage",
+            }),
+            None => Err(HostDBError::Exhausted),
+        }
+    }
+
+    fn block_hash(&mut self, number: revm::primitives::U256) -> Result<B256, Self::Error> {
+        if let Some(history) = self.block_hash_history {
+            let num = u64::try_from(number).unwrap_or(u64::MAX);
+            let slot = revm::primitives::U256::from_limbs(history.slot(num).0);
+            let value = self.storage(address::to_revm(history.address), slot)?;
+            return Ok(B256::from(value.to_be_bytes::<32>()));
+        }
+
+        let number = PU256(number.into_limbs());
+        match self.entries.pop_front() {
+            Some(WitnessEntry::BlockHash(n, hash)) if n == number => Ok(hash),
+            Some(_) => Err(HostDBError::OutOfOrder {
+                expected: "block hash",
+            }),
+            None => Err(HostDBError::Exhausted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_entry_round_trips_with_info_present() {
+        let entry = WitnessEntry::Account(
+            EvmAddress::repeat_byte(0xAA),
+            Some(AccountInfo {
+                balance: revm::primitives::U256::from(42),
+                nonce: 7,
+                ..Default::default()
+            }),
+        );
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn account_entry_round_trips_with_info_absent() {
+        let entry = WitnessEntry::Account(EvmAddress::repeat_byte(0xAA), None);
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn code_entry_round_trips() {
+        let entry = WitnessEntry::Code(B256::repeat_byte(0x11), vec![0x60, 0x00, 0x60, 0x00]);
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn code_entry_round_trips_with_empty_code() {
+        let entry = WitnessEntry::Code(B256::repeat_byte(0x11), vec![]);
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn storage_entry_round_trips() {
+        let entry = WitnessEntry::Storage(
+            EvmAddress::repeat_byte(0xBB),
+            PU256::from(12345u64),
+            PU256::MAX,
+        );
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn block_hash_entry_round_trips() {
+        let entry = WitnessEntry::BlockHash(PU256::from(100u64), B256::repeat_byte(0x22));
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn code_proof_entry_round_trips() {
+        let entry = WitnessEntry::CodeProof(
+            vec![0x60, 0x00, 0x60, 0x00],
+            MerkleProof {
+                leaf_index: 3,
+                siblings: vec![H256::repeat_byte(0x11), H256::repeat_byte(0x22)],
+            },
+        );
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn code_proof_entry_round_trips_with_no_siblings() {
+        let entry = WitnessEntry::CodeProof(
+            vec![],
+            MerkleProof {
+                leaf_index: 0,
+                siblings: vec![],
+            },
+        );
+
+        assert_eq!(WitnessEntry::decode(&entry.encode()).unwrap(), entry);
+    }
+
+    #[test]
+    fn code_by_hash_verifies_against_the_configured_registry_root_when_opted_in() {
+        use crate::trie::MerkleTree;
+
+        let code = vec![0x60, 0x01, 0x60, 0x02, 0x01];
+        let other_code = vec![0xfe];
+        let code_leaf = H256::from(Keccak256::digest(&code).as_ref());
+        let other_leaf = H256::from(Keccak256::digest(&other_code).as_ref());
+        let tree = MerkleTree::build(&[code_leaf, other_leaf]);
+        let proof = tree.prove(0);
+
+        let mut db = HostDB::new(vec![WitnessEntry::CodeProof(code.clone(), proof)])
+            .with_code_registry(tree.root());
+
+        let code_hash = B256::from(code_leaf.0);
+        let bytecode = db.code_by_hash(code_hash).unwrap();
+        assert_eq!(bytecode.original_bytes().as_ref(), code.as_slice());
+    }
+
+    #[test]
+    fn code_by_hash_rejects_a_proof_that_does_not_verify_against_the_registry_root() {
+        use crate::trie::MerkleTree;
+
+        let code = vec![0x60, 0x01, 0x60, 0x02, 0x01];
+        let other_code = vec![0xfe];
+        let code_leaf = H256::from(Keccak256::digest(&code).as_ref());
+        let other_leaf = H256::from(Keccak256::digest(&other_code).as_ref());
+        let tree = MerkleTree::build(&[code_leaf, other_leaf]);
+        // A proof for the wrong leaf index won't verify the supplied code.
+        let wrong_proof = tree.prove(1);
+
+        let mut db = HostDB::new(vec![WitnessEntry::CodeProof(code.clone(), wrong_proof)])
+            .with_code_registry(tree.root());
+
+        let code_hash = B256::from(code_leaf.0);
+        assert_eq!(
+            db.code_by_hash(code_hash),
+            Err(HostDBError::InvalidCodeProof(code_hash))
+        );
+    }
+
+    #[test]
+    fn code_by_hash_rejects_code_that_does_not_hash_to_the_claimed_hash() {
+        let claimed_code = vec![0x60, 0x01, 0x60, 0x02, 0x01];
+        let code_hash = B256::from(Keccak256::digest(&claimed_code).as_ref());
+        // The entry claims `code_hash` but carries different bytes.
+        let actual_code = vec![0xfe];
+
+        let mut db = HostDB::new(vec![WitnessEntry::Code(code_hash, actual_code)]);
+
+        assert_eq!(
+            db.code_by_hash(code_hash),
+            Err(HostDBError::CodeHashMismatch(code_hash))
+        );
+    }
+
+    #[test]
+    fn basic_strips_whatever_code_the_witness_entry_carried() {
+        let address = EvmAddress::repeat_byte(0xAA);
+        let smuggled_code_hash = B256::from(Keccak256::digest([0x60, 0x00]).as_ref());
+        let mut db = HostDB::new(vec![WitnessEntry::Account(
+            address,
+            Some(AccountInfo {
+                code_hash: smuggled_code_hash,
+                code: Some(Bytecode::new_raw(vec![0x60, 0x00].into())),
+                ..Default::default()
+            }),
+        )]);
+
+        let info = db.basic(address::to_revm(address)).unwrap().unwrap();
+
+        // `code_hash` is passed through untouched — only `code_by_hash` can
+        // ever turn it into trusted bytes — but `code` itself never is.
+        assert_eq!(info.code_hash, smuggled_code_hash);
+        assert_eq!(info.code, None);
+    }
+
+    /// An EOA's call never reaches [`HostDB::code_by_hash`] at all: revm
+    /// recognizes [`revm::primitives::KECCAK_EMPTY`] and fills in empty code
+    /// itself, so a witness for a transfer between two EOAs needs no `Code`
+    /// entry for either side.
+    #[test]
+    fn a_transfer_between_two_eoas_records_and_replays_with_no_code_entry() {
+        use crate::evm::run_standalone;
+        use crate::host::store::{InMemoryStateStore, WitnessDB};
+        use crate::tx::{Eip1559Tx, EvmTransaction, TxCommon};
+        use revm::primitives::U256 as RU256;
+
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut store = InMemoryStateStore::default();
+        store.accounts.insert(
+            sender,
+            AccountInfo {
+                balance: RU256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+
+        let tx = EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 100_000,
+                to: Some(recipient),
+                value: PU256::from(1_000u64),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        });
+        let block = revm::primitives::BlockEnv {
+            gas_limit: RU256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let config = crate::config::RollupConfig::default();
+
+        let mut witness_db = WitnessDB::new(store);
+        run_standalone(sender, &tx, block.clone(), &config, &mut witness_db).unwrap();
+        let witness = witness_db.into_witness();
+        assert!(
+            !witness.iter().any(|e| matches!(e, WitnessEntry::Code(..))),
+            "an EOA-to-EOA transfer should never need a code entry"
+        );
+
+        let mut replay_db = HostDB::new(witness);
+        let receipt = run_standalone(sender, &tx, block, &config, &mut replay_db).unwrap();
+        assert_eq!(receipt.outcome, crate::evm::TxOutcome::Success);
+    }
+
+    /// A call into a contract does need [`HostDB::code_by_hash`]: the
+    /// witness carries a dedicated `Code` entry, and replay consumes it (and
+    /// verifies it) rather than trusting any code the `Account` entry might
+    /// have carried.
+    #[test]
+    fn a_call_into_a_contract_records_and_replays_a_code_entry() {
+        use crate::evm::run_standalone;
+        use crate::host::store::{InMemoryStateStore, WitnessDB};
+        use crate::tx::{Eip1559Tx, EvmTransaction, TxCommon};
+        use revm::primitives::U256 as RU256;
+
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let contract = EvmAddress::repeat_byte(0xCC);
+        // PUSH1 0x00 PUSH1 0x00 RETURN: returns empty output, succeeds.
+        let code = vec![0x60, 0x00, 0x60, 0x00, 0xf3];
+        let code_hash = B256::from(Keccak256::digest(&code).as_ref());
+
+        let mut store = InMemoryStateStore::default();
+        store.accounts.insert(
+            sender,
+            AccountInfo {
+                balance: RU256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        store.accounts.insert(
+            contract,
+            AccountInfo {
+                code_hash,
+                // `None`, not `Default::default()`'s empty bytecode — a
+                // store reporting an account's persistent fields doesn't
+                // also hand back its code; that only ever comes from
+                // `code_by_hash`.
+                code: None,
+                ..Default::default()
+            },
+        );
+        store.code.insert(code_hash, code);
+
+        let tx = EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 100_000,
+                to: Some(contract),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        });
+        let block = revm::primitives::BlockEnv {
+            gas_limit: RU256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let config = crate::config::RollupConfig::default();
+
+        let mut witness_db = WitnessDB::new(store);
+        run_standalone(sender, &tx, block.clone(), &config, &mut witness_db).unwrap();
+        let witness = witness_db.into_witness();
+        assert!(
+            witness
+                .iter()
+                .any(|e| matches!(e, WitnessEntry::Code(h, _) if *h == code_hash)),
+            "a call into a contract should record that contract's code"
+        );
+
+        let mut replay_db = HostDB::new(witness);
+        let receipt = run_standalone(sender, &tx, block, &config, &mut replay_db).unwrap();
+        assert_eq!(receipt.outcome, crate::evm::TxOutcome::Success);
+    }
+
+    #[test]
+    fn decode_rejects_an_unknown_tag() {
+        assert_eq!(
+            WitnessEntry::decode(&[0xFF]),
+            Err(WitnessEntryCodecError::UnknownTag(0xFF))
+        );
+    }
+
+    #[test]
+    fn decode_rejects_a_truncated_entry() {
+        let entry = WitnessEntry::BlockHash(PU256::from(100u64), B256::repeat_byte(0x22));
+        let mut encoded = entry.encode();
+        encoded.truncate(encoded.len() - 1);
+
+        assert!(matches!(
+            WitnessEntry::decode(&encoded),
+            Err(WitnessEntryCodecError::Truncated { .. })
+        ));
+    }
+
+    /// A ring-buffer history write (a plain `SSTORE` to `history.address`'s
+    /// slot for a given block number) has no dedicated representation in
+    /// [`crate::log::EvmStateLog`] — it's journaled as an ordinary
+    /// [`crate::log::Access::Write`] on that account's `storage`, the same
+    /// as any other slot. There's no `Blockhash` case in
+    /// [`crate::log::Access`] for it to take instead, so the log's type
+    /// can't represent (and so can't mishandle) a block-hash write at all.
+    #[test]
+    fn a_ring_buffer_history_write_is_journaled_as_an_ordinary_storage_write() {
+        use crate::evm::apply_transactions;
+        use crate::log::Access;
+        use crate::tx::{Eip1559Tx, EvmTransaction, TxCommon};
+        use primitive_types::H256;
+        use revm::db::InMemoryDB;
+        use revm::primitives::{AccountInfo, Bytecode, U256 as RU256};
+
+        let history = BlockHashHistory {
+            address: EvmAddress::repeat_byte(0xCC),
+            window: 8192,
+        };
+        let sender = EvmAddress::repeat_byte(0xAA);
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: RU256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        // PUSH1 0xAB PUSH1 0x64 SSTORE STOP: writes 0xAB to slot 100, the
+        // ring buffer slot for block number 100 under this history's window.
+        let code = hex::decode("60ab60645500").unwrap();
+        db.insert_account_info(
+            address::to_revm(history.address),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                ..Default::default()
+            },
+        );
+
+        let tx = EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 100_000,
+                to: Some(history.address),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        });
+
+        let block = revm::primitives::BlockEnv {
+            gas_limit: RU256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let (tree, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, tx)],
+            block,
+            revm::primitives::SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+        assert_eq!(tree.includes, vec![true]);
+
+        let entry = log
+            .accounts
+            .iter()
+            .find(|e| e.address == history.address)
+            .expect("the history account is in the log");
+        let mut slot_bytes = [0u8; 32];
+        history.slot(100).to_big_endian(&mut slot_bytes);
+        let slot = H256::from(slot_bytes);
+        let (_, access) = entry
+            .storage
+            .iter()
+            .find(|(key, _)| *key == slot)
+            .expect("the ring buffer slot was journaled as ordinary storage");
+        assert_eq!(*access, Access::Write(Some(PU256::from(0xABu64))));
+    }
+}
@@ -0,0 +1,168 @@
+//! A mempool-level nonce tracker for the host's block builder.
+//!
+//! [`crate::evm::filter_transactions`] only knows a sender's nonce chain
+//! within the batch it's handed, starting from whatever `db` currently
+//! reports. Across blocks that's not enough: a block just applied to the
+//! mempool's in-flight view hasn't necessarily landed on `db` yet, so
+//! building the next block from `db` alone risks re-offering a nonce the
+//! previous block already used. `NonceTracker` is the block builder's own
+//! memory of where each sender's chain actually left off, independent of
+//! `db`.
+
+use std::collections::HashMap;
+
+use revm::Database;
+
+use crate::address::{self, EvmAddress};
+use crate::tx::EvmTransaction;
+
+/// Tracks each sender's next expected nonce across a sequence of applied
+/// blocks.
+#[derive(Debug, Default, Clone)]
+pub struct NonceTracker {
+    next_nonce: HashMap<EvmAddress, u64>,
+}
+
+impl NonceTracker {
+    /// Builds a tracker that hasn't seen any blocks yet — every sender
+    /// falls back to `db` until [`NonceTracker::record_block`] says
+    /// otherwise.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filters `txs` down to the contiguous nonce-chain prefix each sender
+    /// can extend right now, the same as [`crate::evm::filter_transactions`]
+    /// except consulting this tracker's remembered nonce ahead of `db`,
+    /// falling back to `db` only for senders this tracker hasn't recorded a
+    /// block for yet.
+    pub fn filter_for_block<DB: Database>(
+        &self,
+        txs: &[(EvmAddress, EvmTransaction)],
+        db: &mut DB,
+    ) -> Result<Vec<(EvmAddress, EvmTransaction)>, DB::Error> {
+        let mut next_nonce = self.next_nonce.clone();
+        let mut out = Vec::new();
+
+        for (sender, tx) in txs {
+            let expected = match next_nonce.get(sender) {
+                Some(n) => *n,
+                None => {
+                    db.basic(address::to_revm(*sender))?
+                        .unwrap_or_default()
+                        .nonce
+                }
+            };
+
+            if tx.nonce() == expected {
+                out.push((*sender, tx.clone()));
+                next_nonce.insert(*sender, expected + 1);
+            } else {
+                next_nonce.entry(*sender).or_insert(expected);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Records a block's applied transactions, advancing each sender's
+    /// next-expected nonce past what it just used. Call this once a block
+    /// actually commits — not on a block merely built, since a block that
+    /// never lands shouldn't shift where the next one starts.
+    pub fn record_block(&mut self, applied: &[(EvmAddress, EvmTransaction)]) {
+        for (sender, tx) in applied {
+            let next = tx.nonce() + 1;
+            self.next_nonce
+                .entry(*sender)
+                .and_modify(|n| *n = next.max(*n))
+                .or_insert(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::{Eip1559Tx, TxCommon};
+    use revm::db::InMemoryDB;
+    use revm::primitives::{AccountInfo, U256};
+
+    fn tx(nonce: u64) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce,
+                gas_limit: 21_000,
+                to: Some(EvmAddress::repeat_byte(0xBB)),
+                value: primitive_types::U256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: primitive_types::U256::from(10u64),
+            max_priority_fee_per_gas: primitive_types::U256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    fn funded_db(sender: EvmAddress) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+        db
+    }
+
+    #[test]
+    fn stale_nonces_are_excluded_once_the_prior_block_is_recorded() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        let mut tracker = NonceTracker::new();
+
+        let first_block = vec![(sender, tx(0)), (sender, tx(1))];
+        let filtered = tracker.filter_for_block(&first_block, &mut db).unwrap();
+        assert_eq!(filtered.len(), 2);
+        tracker.record_block(&filtered);
+
+        // `db` still reports nonce 0 — only the tracker knows the first
+        // block used nonces 0 and 1.
+        let next_block = vec![(sender, tx(0)), (sender, tx(2))];
+        let filtered = tracker.filter_for_block(&next_block, &mut db).unwrap();
+
+        assert_eq!(
+            filtered
+                .iter()
+                .map(|(_, tx)| tx.nonce())
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn an_unrecorded_sender_falls_back_to_the_db_nonce() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = funded_db(sender);
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                nonce: 5,
+                ..Default::default()
+            },
+        );
+        let tracker = NonceTracker::new();
+
+        let txs = vec![(sender, tx(5)), (sender, tx(6))];
+        let filtered = tracker.filter_for_block(&txs, &mut db).unwrap();
+
+        assert_eq!(
+            filtered
+                .iter()
+                .map(|(_, tx)| tx.nonce())
+                .collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+    }
+}
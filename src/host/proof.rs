@@ -0,0 +1,284 @@
+//! Per-transaction inclusion proofs.
+//!
+//! A verifier who trusts a block's post-state root but wants to check only
+//! one transaction's contribution to it shouldn't have to download and
+//! replay the whole block's witness. [`apply_transactions_with_proofs`]
+//! captures each included transaction's own witness as it actually executes
+//! — in order, against real state — so [`TxProof::verify`] can later replay
+//! just that transaction on its own via [`HostDB`] and confirm it produced
+//! the claimed receipt.
+
+use revm::primitives::{Account, AccountInfo, Address, BlockEnv, Bytecode, SpecId, B256};
+use revm::{Database, DatabaseCommit, EVM};
+
+use crate::address::{self, EvmAddress};
+use crate::config::RollupConfig;
+use crate::convert;
+use crate::evm::{self, TxOutcome, TxReceipt, TxTree};
+use crate::host::{HostDB, HostDBError, WitnessEntry};
+use crate::log::EvmStateLog;
+use crate::tx::EvmTransaction;
+
+/// A [`Database`] that wraps another one, recording every read into a
+/// witness while forwarding everything else — including commits —
+/// unchanged. Used to capture one transaction's own witness as it executes
+/// against real, already-committed state, without disturbing that state's
+/// continuity into the next transaction.
+struct WitnessingDB<'a, DB> {
+    inner: &'a mut DB,
+    entries: Vec<WitnessEntry>,
+}
+
+impl<'a, DB: Database> Database for WitnessingDB<'a, DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.inner.basic(address)?;
+        self.entries.push(WitnessEntry::Account(
+            address::from_revm(address),
+            info.clone(),
+        ));
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.inner.code_by_hash(code_hash)?;
+        self.entries.push(WitnessEntry::Code(
+            code_hash,
+            code.original_bytes().to_vec(),
+        ));
+        Ok(code)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: revm::primitives::U256,
+    ) -> Result<revm::primitives::U256, Self::Error> {
+        let value = self.inner.storage(address, index)?;
+        self.entries.push(WitnessEntry::Storage(
+            address::from_revm(address),
+            primitive_types::U256(index.into_limbs()),
+            primitive_types::U256(value.into_limbs()),
+        ));
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: revm::primitives::U256) -> Result<B256, Self::Error> {
+        let hash = self.inner.block_hash(number)?;
+        self.entries.push(WitnessEntry::BlockHash(
+            primitive_types::U256(number.into_limbs()),
+            hash,
+        ));
+        Ok(hash)
+    }
+}
+
+impl<'a, DB: DatabaseCommit> DatabaseCommit for WitnessingDB<'a, DB> {
+    fn commit(&mut self, changes: revm::primitives::HashMap<Address, Account>) {
+        self.inner.commit(changes)
+    }
+}
+
+/// One transaction's inclusion proof: its own witness, plus the receipt it
+/// produced — enough for [`TxProof::verify`] to replay it independently of
+/// the rest of the block it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxProof {
+    pub caller: EvmAddress,
+    pub tx: EvmTransaction,
+    pub witness: Vec<WitnessEntry>,
+    pub receipt: TxReceipt,
+}
+
+/// Errors raised while verifying a [`TxProof`].
+#[derive(Debug, thiserror::Error)]
+pub enum TxProofError {
+    /// Replaying the proof's witness through [`HostDB`] failed outright —
+    /// the witness doesn't match the transaction it's claimed for.
+    #[error("replay failed: {0}")]
+    Replay(#[from] evm::TxError<HostDBError>),
+    /// Replay succeeded, but didn't reproduce the claimed receipt.
+    #[error("receipt mismatch: expected {expected:?}, got {actual:?}")]
+    ReceiptMismatch {
+        expected: Box<TxReceipt>,
+        actual: Box<TxReceipt>,
+    },
+}
+
+impl TxProof {
+    /// Replays this proof's transaction purely from its own recorded
+    /// witness, against `block` and `spec_id`, and confirms it reproduces
+    /// the claimed receipt.
+    pub fn verify(&self, block: BlockEnv, spec_id: SpecId) -> Result<(), TxProofError> {
+        let mut replay_db = HostDB::new(self.witness.clone());
+        let config = RollupConfig {
+            spec_id,
+            ..RollupConfig::default()
+        };
+        let actual = evm::run_standalone(self.caller, &self.tx, block, &config, &mut replay_db)?;
+        if actual == self.receipt {
+            Ok(())
+        } else {
+            Err(TxProofError::ReceiptMismatch {
+                expected: Box::new(self.receipt.clone()),
+                actual: Box::new(actual),
+            })
+        }
+    }
+}
+
+/// Like [`evm::apply_transactions`], but also returns one [`TxProof`] per
+/// transaction offered, aligned by index with `txs` (and with the returned
+/// [`TxTree::includes`]) — `None` wherever that transaction wasn't included.
+/// As in `apply_transactions`, a transaction revm rejects is skipped, and the
+/// first one that would push cumulative gas past `block.gas_limit` ends the
+/// loop. Unlike it, this takes no deposits, withdrawals or log-access cap,
+/// and its log doesn't record access-list reads.
+pub fn apply_transactions_with_proofs<DB>(
+    txs: &[(EvmAddress, EvmTransaction)],
+    block: BlockEnv,
+    spec_id: SpecId,
+    db: &mut DB,
+) -> (TxTree, EvmStateLog, Vec<Option<TxProof>>)
+where
+    DB: Database + DatabaseCommit,
+{
+    let mut includes = vec![false; txs.len()];
+    let mut cumulative_gas: u64 = 0;
+    let mut log = std::collections::HashMap::new();
+    let mut proofs = vec![None; txs.len()];
+
+    for (i, (caller, tx)) in txs.iter().enumerate() {
+        if cumulative_gas.saturating_add(tx.gas_limit())
+            > u64::try_from(block.gas_limit).unwrap_or(u64::MAX)
+        {
+            break;
+        }
+
+        let mut witnessing = WitnessingDB {
+            inner: db,
+            entries: Vec::new(),
+        };
+        let mut evm: EVM<&mut WitnessingDB<'_, DB>> = EVM::new();
+        evm.env.cfg.spec_id = spec_id;
+        evm.env.block = block.clone();
+        tx.add_to_env(*caller, &mut evm.env.tx);
+        evm.db = Some(&mut witnessing);
+
+        let result_and_state = match evm.transact() {
+            Ok(r) => r,
+            // Same as `apply_transactions`: an invalid transaction is
+            // simply excluded rather than aborting the whole bundle.
+            Err(_) => continue,
+        };
+        let effective_gas_price = convert::u256_from_revm(evm.env.effective_gas_price());
+        drop(evm);
+
+        let gas_used = result_and_state.result.gas_used();
+        let outcome = match result_and_state.result.clone() {
+            revm::primitives::ExecutionResult::Success { .. } => TxOutcome::Success,
+            revm::primitives::ExecutionResult::Revert { output, .. } => {
+                TxOutcome::Reverted(output.to_vec())
+            }
+            revm::primitives::ExecutionResult::Halt { reason, .. } => TxOutcome::Halted(reason),
+        };
+        let receipt = TxReceipt {
+            gas_used,
+            outcome,
+            effective_gas_price,
+        };
+
+        witnessing.commit(result_and_state.state.clone());
+        evm::record_commit(&mut log, result_and_state.state, spec_id);
+
+        proofs[i] = Some(TxProof {
+            caller: *caller,
+            tx: tx.clone(),
+            witness: witnessing.entries,
+            receipt,
+        });
+        cumulative_gas += tx.gas_limit();
+        includes[i] = true;
+    }
+
+    let mut accounts: Vec<_> = log.into_values().collect();
+    accounts.sort_by_key(|e| e.address);
+    for entry in &mut accounts {
+        entry.storage.sort_by_key(|(k, _)| *k);
+    }
+
+    (
+        TxTree { includes },
+        EvmStateLog {
+            accounts,
+            sequencer_balances: vec![],
+        },
+        proofs,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::{Eip1559Tx, TxCommon};
+    use primitive_types::U256 as PU256;
+    use revm::db::InMemoryDB;
+    use revm::primitives::U256;
+
+    fn tx(nonce: u64, to: EvmAddress, value: PU256) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce,
+                gas_limit: 21_000,
+                to: Some(to),
+                value,
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    #[test]
+    fn tx_proof_for_one_of_three_transactions_verifies_independently() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let txs = vec![
+            (sender, tx(0, recipient, PU256::from(100u64))),
+            (sender, tx(1, recipient, PU256::from(200u64))),
+            (sender, tx(2, recipient, PU256::from(300u64))),
+        ];
+
+        let (tree, _log, proofs) =
+            apply_transactions_with_proofs(&txs, block.clone(), SpecId::LATEST, &mut db);
+
+        assert_eq!(tree.includes, vec![true, true, true]);
+        assert_eq!(proofs.len(), 3);
+
+        let middle = proofs[1]
+            .as_ref()
+            .expect("transaction 1 should have a proof");
+        assert_eq!(middle.caller, sender);
+        assert_eq!(middle.tx, txs[1].1);
+        assert_eq!(middle.receipt.outcome, TxOutcome::Success);
+        assert!(!middle.witness.is_empty());
+
+        middle.verify(block, SpecId::LATEST).unwrap();
+    }
+}
@@ -0,0 +1,490 @@
+//! A pluggable source of real chain state for the host side, so the same
+//! witness-recording [`WitnessDB`] can run against an in-memory store in
+//! tests and against a real backend in production, without the EVM driver
+//! code caring which.
+//!
+//! [`StateStore`] also offers a lightweight `exists`/`balance` read path
+//! alongside its full [`StateStore::get_account`], for host-side callers
+//! that don't need (and shouldn't have to pay for) the rest of an account —
+//! in particular its code.
+
+use std::collections::HashMap;
+
+use primitive_types::U256 as PU256;
+use revm::primitives::{AccountInfo, Bytecode, B256};
+use revm::Database;
+use thiserror::Error;
+
+use crate::address::{self, EvmAddress};
+use crate::convert::{self, ConvertError};
+use crate::host::{BlockHashHistory, WitnessEntry};
+
+/// A host-side source of real chain state: everything revm needs to
+/// execute a transaction, plus Merkle proof retrieval for backends that can
+/// supply one.
+pub trait StateStore {
+    type Error;
+
+    /// Fetches an account's info, or `None` if it doesn't exist.
+    fn get_account(&self, address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error>;
+
+    /// Lightweight existence check, for a caller that only needs a
+    /// `BALANCE`/`EXTCODESIZE`-style yes-or-no answer and doesn't want to
+    /// pay for the rest of the account (in particular its code) the way
+    /// [`StateStore::get_account`] does. The default implementation just
+    /// discards everything but the `Option` from `get_account`; override it
+    /// if the backend can answer more cheaply (e.g. an RPC backend could use
+    /// a plain `eth_getBalance` to learn existence without ever fetching
+    /// code).
+    fn account_exists(&self, address: EvmAddress) -> Result<bool, Self::Error> {
+        Ok(self.get_account(address)?.is_some())
+    }
+
+    /// Lightweight balance read, for the same kind of caller as
+    /// [`StateStore::account_exists`] that only needs a `BALANCE`-style
+    /// answer. `None` if the account doesn't exist. The default
+    /// implementation defers to `get_account`; see `account_exists` for why
+    /// a backend would want to override it.
+    fn get_balance(&self, address: EvmAddress) -> Result<Option<PU256>, Self::Error> {
+        Ok(self
+            .get_account(address)?
+            .map(|info| convert::u256_from_revm(info.balance)))
+    }
+
+    /// Fetches a single storage slot's value, defaulting to zero.
+    fn get_storage(&self, address: EvmAddress, key: PU256) -> Result<PU256, Self::Error>;
+
+    /// Fetches the code stored under `code_hash`.
+    fn get_code(&self, code_hash: B256) -> Result<Vec<u8>, Self::Error>;
+
+    /// Fetches the hash of block `number`.
+    fn get_blockhash(&self, number: u64) -> Result<B256, Self::Error>;
+
+    /// A Merkle proof for `address`'s account and, if given, one of its
+    /// storage slots — for backends that can produce one (e.g. via
+    /// `eth_getProof`), as raw RLP-encoded trie nodes. This crate has no
+    /// trie implementation yet to verify a proof against a root, so callers
+    /// can only stash these for later use.
+    fn get_proof(
+        &self,
+        address: EvmAddress,
+        key: Option<PU256>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error>;
+}
+
+/// An in-memory [`StateStore`], for tests that want to wire up
+/// [`WitnessDB`] without a live node.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryStateStore {
+    pub accounts: HashMap<EvmAddress, AccountInfo>,
+    pub storage: HashMap<(EvmAddress, PU256), PU256>,
+    pub code: HashMap<B256, Vec<u8>>,
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+impl StateStore for InMemoryStateStore {
+    type Error = std::convert::Infallible;
+
+    fn get_account(&self, address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error> {
+        Ok(self.accounts.get(&address).cloned())
+    }
+
+    fn get_storage(&self, address: EvmAddress, key: PU256) -> Result<PU256, Self::Error> {
+        Ok(*self.storage.get(&(address, key)).unwrap_or(&PU256::zero()))
+    }
+
+    fn get_code(&self, code_hash: B256) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.code.get(&code_hash).cloned().unwrap_or_default())
+    }
+
+    fn get_blockhash(&self, number: u64) -> Result<B256, Self::Error> {
+        Ok(self
+            .block_hashes
+            .get(&number)
+            .copied()
+            .unwrap_or(B256::ZERO))
+    }
+
+    fn get_proof(
+        &self,
+        _address: EvmAddress,
+        _key: Option<PU256>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        // No trie to generate a real Merkle proof against; tests that need
+        // proof data should assert against `accounts`/`storage` directly.
+        Ok(vec![])
+    }
+}
+
+/// A stub [`StateStore`] standing in for a real key-value or RPC-backed
+/// backend. Every method errors; filling these in against a real source is
+/// how a production host wires `WitnessDB` up to live chain data (see
+/// [`crate::host::rpc::EthRpcClient`] for the execution-driven approach
+/// this crate already uses for witness generation).
+#[derive(Debug, Default)]
+pub struct UnimplementedStateStore;
+
+/// The error [`UnimplementedStateStore`] always returns.
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("UnimplementedStateStore has no backend wired up")]
+pub struct UnimplementedStateStoreError;
+
+impl StateStore for UnimplementedStateStore {
+    type Error = UnimplementedStateStoreError;
+
+    fn get_account(&self, _address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error> {
+        Err(UnimplementedStateStoreError)
+    }
+
+    fn get_storage(&self, _address: EvmAddress, _key: PU256) -> Result<PU256, Self::Error> {
+        Err(UnimplementedStateStoreError)
+    }
+
+    fn get_code(&self, _code_hash: B256) -> Result<Vec<u8>, Self::Error> {
+        Err(UnimplementedStateStoreError)
+    }
+
+    fn get_blockhash(&self, _number: u64) -> Result<B256, Self::Error> {
+        Err(UnimplementedStateStoreError)
+    }
+
+    fn get_proof(
+        &self,
+        _address: EvmAddress,
+        _key: Option<PU256>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Err(UnimplementedStateStoreError)
+    }
+}
+
+impl<S: StateStore> StateStore for &S {
+    type Error = S::Error;
+
+    fn get_account(&self, address: EvmAddress) -> Result<Option<AccountInfo>, Self::Error> {
+        (**self).get_account(address)
+    }
+
+    fn account_exists(&self, address: EvmAddress) -> Result<bool, Self::Error> {
+        (**self).account_exists(address)
+    }
+
+    fn get_balance(&self, address: EvmAddress) -> Result<Option<PU256>, Self::Error> {
+        (**self).get_balance(address)
+    }
+
+    fn get_storage(&self, address: EvmAddress, key: PU256) -> Result<PU256, Self::Error> {
+        (**self).get_storage(address, key)
+    }
+
+    fn get_code(&self, code_hash: B256) -> Result<Vec<u8>, Self::Error> {
+        (**self).get_code(code_hash)
+    }
+
+    fn get_blockhash(&self, number: u64) -> Result<B256, Self::Error> {
+        (**self).get_blockhash(number)
+    }
+
+    fn get_proof(
+        &self,
+        address: EvmAddress,
+        key: Option<PU256>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        (**self).get_proof(address, key)
+    }
+}
+
+/// A [`Database`] that reads from a [`StateStore`] and records every read
+/// as a [`WitnessEntry`], in the order [`super::HostDB`] will replay them.
+/// Running a transaction against a `WitnessDB` (e.g. via
+/// [`crate::evm::run_standalone`]) is how the host builds a witness for the
+/// guest — generic over `StateStore` so the whole path is testable against
+/// an [`InMemoryStateStore`] without a live node.
+pub struct WitnessDB<S> {
+    store: S,
+    entries: Vec<WitnessEntry>,
+    block_hash_history: Option<BlockHashHistory>,
+}
+
+impl<S: StateStore> WitnessDB<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            entries: Vec::new(),
+            block_hash_history: None,
+        }
+    }
+
+    /// Opts this `WitnessDB` into reading `BLOCKHASH` from `history`'s ring
+    /// buffer — via the ordinary [`StateStore::get_storage`] path — instead
+    /// of from [`StateStore::get_blockhash`].
+    pub fn with_block_hash_history(mut self, history: BlockHashHistory) -> Self {
+        self.block_hash_history = Some(history);
+        self
+    }
+
+    /// Consumes this `WitnessDB`, returning the entries recorded for
+    /// everything it was asked to read.
+    pub fn into_witness(self) -> Vec<WitnessEntry> {
+        self.entries
+    }
+
+    /// Checks whether `address` has an account, via
+    /// [`StateStore::account_exists`] — without journaling anything. For a
+    /// witness generator that wants to answer a `BALANCE`/`EXTCODESIZE`-style
+    /// question about an address up front, without paying for the rest of
+    /// the account the way [`Database::basic`] would. The guest-replayed
+    /// journal itself is unaffected either way: an actual `BALANCE` or
+    /// `EXTCODESIZE` the guest executes still goes through `basic`, which
+    /// always journals the complete account for replay soundness.
+    pub fn account_exists(&self, address: EvmAddress) -> Result<bool, WitnessDBError<S::Error>> {
+        self.store
+            .account_exists(address)
+            .map_err(WitnessDBError::Store)
+    }
+
+    /// Reads `address`'s balance via [`StateStore::get_balance`], with the
+    /// same no-journaling caveat as [`WitnessDB::account_exists`].
+    pub fn balance(&self, address: EvmAddress) -> Result<Option<PU256>, WitnessDBError<S::Error>> {
+        self.store
+            .get_balance(address)
+            .map_err(WitnessDBError::Store)
+    }
+}
+
+/// Errors raised while [`WitnessDB`] reads from its underlying
+/// [`StateStore`], plus the one error that's its own: a `BLOCKHASH` query
+/// for a block number too large to be a real block number.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WitnessDBError<E> {
+    #[error(transparent)]
+    Store(E),
+    #[error("block number for BLOCKHASH: {0}")]
+    InvalidBlockNumber(ConvertError),
+}
+
+impl<S: StateStore> Database for WitnessDB<S> {
+    type Error = WitnessDBError<S::Error>;
+
+    fn basic(
+        &mut self,
+        address: revm::primitives::Address,
+    ) -> Result<Option<AccountInfo>, Self::Error> {
+        let addr = address::from_revm(address);
+        let info = self
+            .store
+            .get_account(addr)
+            .map_err(WitnessDBError::Store)?;
+        self.entries.push(WitnessEntry::Account(addr, info.clone()));
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self
+            .store
+            .get_code(code_hash)
+            .map_err(WitnessDBError::Store)?;
+        self.entries
+            .push(WitnessEntry::Code(code_hash, code.clone()));
+        Ok(Bytecode::new_raw(code.into()))
+    }
+
+    fn storage(
+        &mut self,
+        address: revm::primitives::Address,
+        index: revm::primitives::U256,
+    ) -> Result<revm::primitives::U256, Self::Error> {
+        let addr = address::from_revm(address);
+        let key = PU256(index.into_limbs());
+        let value = self
+            .store
+            .get_storage(addr, key)
+            .map_err(WitnessDBError::Store)?;
+        self.entries.push(WitnessEntry::Storage(addr, key, value));
+        Ok(revm::primitives::U256::from_limbs(value.0))
+    }
+
+    fn block_hash(&mut self, number: revm::primitives::U256) -> Result<B256, Self::Error> {
+        let number = convert::u256_from_revm(number);
+        let num = convert::u256_to_u64(number).map_err(WitnessDBError::InvalidBlockNumber)?;
+
+        if let Some(history) = self.block_hash_history {
+            let slot = history.slot(num);
+            let value = self.storage(
+                address::to_revm(history.address),
+                revm::primitives::U256::from_limbs(slot.0),
+            )?;
+            return Ok(B256::from(value.to_be_bytes::<32>()));
+        }
+
+        let hash = self
+            .store
+            .get_blockhash(num)
+            .map_err(WitnessDBError::Store)?;
+        self.entries
+            .push(WitnessEntry::BlockHash(PU256::from(num), hash));
+        Ok(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RollupConfig;
+    use crate::evm::run_standalone;
+    use crate::tx::{Eip1559Tx, TxCommon};
+    use revm::primitives::{BlockEnv, U256};
+
+    fn transfer_tx(to: EvmAddress, value: PU256) -> crate::tx::EvmTransaction {
+        crate::tx::EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(to),
+                value,
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    #[test]
+    fn running_a_transfer_through_an_in_memory_store_records_a_replayable_witness() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+
+        let mut store = InMemoryStateStore::default();
+        store.accounts.insert(
+            sender,
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let tx = transfer_tx(recipient, PU256::from(1_000u64));
+
+        let mut witness_db = WitnessDB::new(store);
+        let receipt = run_standalone(
+            sender,
+            &tx,
+            block,
+            &RollupConfig::default(),
+            &mut witness_db,
+        )
+        .unwrap();
+        assert_eq!(receipt.outcome, crate::evm::TxOutcome::Success);
+
+        let witness = witness_db.into_witness();
+        assert!(!witness.is_empty());
+
+        // The same reads replay cleanly through `HostDB`, confirming
+        // `WitnessDB` recorded them in the order the guest expects.
+        let mut host_db = crate::host::HostDB::new(witness);
+        let sender_info = host_db.basic(address::to_revm(sender)).unwrap();
+        assert_eq!(
+            sender_info.unwrap().balance,
+            U256::from(1_000_000_000_000u64)
+        );
+    }
+
+    #[test]
+    fn probing_existence_and_balance_through_the_lightweight_helpers_does_not_change_the_recorded_witness(
+    ) {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+
+        let mut store = InMemoryStateStore::default();
+        store.accounts.insert(
+            sender,
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let tx = transfer_tx(recipient, PU256::from(1_000u64));
+
+        let mut plain_db = WitnessDB::new(store.clone());
+        let plain_receipt = run_standalone(
+            sender,
+            &tx,
+            block.clone(),
+            &RollupConfig::default(),
+            &mut plain_db,
+        )
+        .unwrap();
+
+        let mut probed_db = WitnessDB::new(store);
+        // A host deciding whether this access-list entry is even worth a
+        // full fetch probes existence and balance first...
+        assert!(probed_db.account_exists(sender).unwrap());
+        assert_eq!(
+            probed_db.balance(sender).unwrap(),
+            Some(PU256::from(1_000_000_000_000u64))
+        );
+        // ...and the actual execution that follows journals exactly the
+        // same witness either way.
+        let probed_receipt =
+            run_standalone(sender, &tx, block, &RollupConfig::default(), &mut probed_db).unwrap();
+
+        assert_eq!(plain_receipt.outcome, probed_receipt.outcome);
+        assert_eq!(plain_db.into_witness(), probed_db.into_witness());
+    }
+
+    #[test]
+    fn block_hash_with_history_reads_the_ring_buffer_slot_and_journals_it_as_storage() {
+        let history = BlockHashHistory {
+            address: EvmAddress::repeat_byte(0xCC),
+            window: 8192,
+        };
+        let expected_hash = B256::repeat_byte(0x42);
+        let slot = history.slot(100);
+
+        let mut store = InMemoryStateStore::default();
+        store.storage.insert(
+            (history.address, slot),
+            PU256::from_big_endian(expected_hash.as_slice()),
+        );
+
+        let mut witness_db = WitnessDB::new(store).with_block_hash_history(history);
+        let hash = witness_db.block_hash(U256::from(100u64)).unwrap();
+        assert_eq!(hash, expected_hash);
+
+        let witness = witness_db.into_witness();
+        assert_eq!(
+            witness,
+            vec![WitnessEntry::Storage(
+                history.address,
+                slot,
+                PU256::from_big_endian(expected_hash.as_slice())
+            )]
+        );
+    }
+
+    #[test]
+    fn block_hash_rejects_a_block_number_that_overflows_u64() {
+        let store = InMemoryStateStore::default();
+        let mut witness_db = WitnessDB::new(store);
+
+        let huge = U256::from(u64::MAX) + U256::from(1u64);
+        assert_eq!(
+            witness_db.block_hash(huge),
+            Err(WitnessDBError::InvalidBlockNumber(
+                ConvertError::U256OverflowsU64 {
+                    value: convert::u256_from_revm(huge)
+                }
+            ))
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Applies a [`StateUpdate`](crate::log::update::StateUpdate) blob to an
+//! [`InMemoryStateStore`], for a full node that wants to keep its own
+//! database in sync with chain state after a proven block without
+//! re-executing it.
+
+use primitive_types::U256 as PU256;
+
+use crate::log::update::{decode_state_update, InfoUpdate, StateUpdateError};
+
+use super::store::InMemoryStateStore;
+
+/// Decodes `blob` (as produced by
+/// [`EvmStateLog::state_update_blob`](crate::log::EvmStateLog::state_update_blob))
+/// and applies it to `store` in place: each account's info is set or
+/// removed, its newly-deployed code (if any) is persisted under its code
+/// hash, and its touched storage slots are set or cleared.
+pub fn apply_state_update_blob(
+    store: &mut InMemoryStateStore,
+    blob: &[u8],
+) -> Result<(), StateUpdateError> {
+    let update = decode_state_update(blob)?;
+
+    for account in update.accounts {
+        match account.info {
+            InfoUpdate::Unchanged => {}
+            InfoUpdate::Set(info) => {
+                store.accounts.insert(account.address, info);
+            }
+            InfoUpdate::Deleted => {
+                store.accounts.remove(&account.address);
+            }
+        }
+
+        if let Some(code) = account.code {
+            if let Some(info) = store.accounts.get(&account.address) {
+                store.code.insert(info.code_hash, code);
+            }
+        }
+
+        for (key, value) in account.storage {
+            let key = PU256::from_big_endian(key.as_bytes());
+            match value {
+                Some(value) => {
+                    store.storage.insert((account.address, key), value);
+                }
+                None => {
+                    store.storage.remove(&(account.address, key));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::EvmAddress;
+    use crate::evm::apply_transactions;
+    use crate::log::EvmStateLog;
+    use crate::tx::{Eip1559Tx, EvmTransaction, TxCommon};
+    use revm::db::InMemoryDB;
+    use revm::primitives::{AccountInfo, BlockEnv, SpecId, U256};
+
+    fn transfer_tx(to: EvmAddress, value: PU256) -> EvmTransaction {
+        EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(to),
+                value,
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })
+    }
+
+    #[test]
+    fn applying_a_transfers_blob_updates_both_accounts_in_an_in_memory_store() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let recipient = EvmAddress::repeat_byte(0xBB);
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            crate::address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+
+        let block = BlockEnv {
+            gas_limit: U256::from(1_000_000u64),
+            ..Default::default()
+        };
+        let tx = transfer_tx(recipient, PU256::from(1_000u64));
+
+        let (_, log) = apply_transactions(
+            &[],
+            &[],
+            &[(sender, tx)],
+            block,
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        let mut store = InMemoryStateStore::default();
+        apply_state_update_blob(&mut store, &log.state_update_blob()).unwrap();
+
+        assert_eq!(
+            store.accounts.get(&recipient).unwrap().balance,
+            U256::from(1_000u64)
+        );
+        assert!(store.accounts.get(&sender).unwrap().balance < U256::from(1_000_000_000_000u64));
+        assert_eq!(store.accounts.get(&sender).unwrap().nonce, 1);
+    }
+
+    #[test]
+    fn applying_a_deletion_blob_removes_the_account_from_the_store() {
+        let address = EvmAddress::repeat_byte(0xAA);
+        let mut store = InMemoryStateStore::default();
+        store.accounts.insert(address, AccountInfo::default());
+
+        let log = EvmStateLog {
+            accounts: vec![crate::log::AccountLogEntry {
+                address,
+                info: crate::log::Access::Write(None),
+                code: None,
+                storage: vec![],
+                storage_root: None,
+                created: false,
+            }],
+            sequencer_balances: vec![],
+        };
+
+        apply_state_update_blob(&mut store, &log.state_update_blob()).unwrap();
+
+        assert!(!store.accounts.contains_key(&address));
+    }
+}
@@ -0,0 +1,321 @@
+//! The claim a guest proof commits to its journal.
+//!
+//! A proof of bundle execution binds three commitments: the pre-state
+//! root it started from, the post-state root it produced, and a
+//! commitment to the exact bundle of transactions it processed. Binding
+//! all three lets a verifier check a proof against specific on-chain or
+//! DA-posted data, rather than trusting the prover's choice of inputs.
+
+use primitive_types::H256;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// The tuple a guest's `main` commits to the journal: `(prev, post,
+/// bundle_commitment, block_number, parent_block_commitment, timestamp)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct JournalClaim {
+    /// The state root the guest started execution from.
+    #[serde(with = "crate::codec::h256")]
+    pub prev: H256,
+    /// The state root the guest produced after applying the bundle.
+    #[serde(with = "crate::codec::h256")]
+    pub post: H256,
+    /// [`crate::bundle::Bundle::commitment`] of the bundle applied.
+    #[serde(with = "crate::codec::h256")]
+    pub bundle_commitment: H256,
+    /// This block's position in the chain, starting from zero at genesis.
+    pub block_number: u64,
+    /// [`JournalClaim::commitment`] of the parent block's claim — binding
+    /// this proof to one specific predecessor, not just to a state root it
+    /// happens to share with the real parent.
+    #[serde(with = "crate::codec::h256")]
+    pub parent_block_commitment: H256,
+    /// This block's timestamp. [`verify_chain`] checks this against the
+    /// parent claim's own `timestamp`, rejecting a chain that goes
+    /// backwards in time.
+    pub timestamp: u64,
+}
+
+/// Errors raised while decoding a journal.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum JournalError {
+    /// The journal's bytes didn't decode as a [`JournalClaim`].
+    #[error("malformed journal: {0}")]
+    Malformed(String),
+    /// The journal decoded, but under a version other than
+    /// [`crate::codec::ENCODING_VERSION`].
+    #[error("unsupported journal encoding version {0}")]
+    UnsupportedVersion(u8),
+}
+
+impl JournalClaim {
+    /// Encodes this claim into the bytes a guest would commit to the
+    /// journal, prefixed with [`crate::codec::ENCODING_VERSION`] so a
+    /// future decoder can reject a journal from an encoder it predates
+    /// instead of misreading it.
+    pub fn encode(&self) -> Vec<u8> {
+        crate::codec::versioned(
+            bincode::serialize(self).expect("JournalClaim is always serializable"),
+        )
+    }
+
+    /// Decodes a claim back out of a committed journal.
+    pub fn decode(journal: &[u8]) -> Result<JournalClaim, JournalError> {
+        let body =
+            crate::codec::strip_version(journal).map_err(JournalError::UnsupportedVersion)?;
+        bincode::deserialize(body).map_err(|e| JournalError::Malformed(e.to_string()))
+    }
+
+    /// A commitment to this exact claim: the Keccak256 hash of its encoded
+    /// bytes. What the next block's [`JournalClaim::parent_block_commitment`]
+    /// binds to, so a chain of claims identifies one specific lineage of
+    /// blocks rather than just a chain of state roots.
+    pub fn commitment(&self) -> H256 {
+        H256::from(Keccak256::digest(self.encode()).as_ref())
+    }
+}
+
+/// Errors [`verify_chain`] can report against a single link in the chain.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ChainError {
+    /// This claim's `prev` doesn't match the previous claim's `post`, so
+    /// the two blocks' state don't chain.
+    #[error("block {index}'s prev state root does not match the previous block's post")]
+    BrokenStateLink { index: usize },
+    /// This claim's `parent_block_commitment` doesn't match the previous
+    /// claim's own [`JournalClaim::commitment`], so this isn't actually a
+    /// proof of the previous block's successor.
+    #[error("block {index}'s parent block commitment does not match the previous block's claim")]
+    BrokenParentCommitment { index: usize },
+    /// This claim's `block_number` isn't exactly one more than the
+    /// previous claim's, so the chain has a gap or goes backwards.
+    #[error("block {index}'s block number is not the previous block's plus one")]
+    NonSequentialBlockNumber { index: usize },
+    /// This claim's `timestamp` doesn't come after the previous claim's —
+    /// or, when `allow_equal_timestamps` is `false`, doesn't come strictly
+    /// after it — so the chain goes backwards (or stalls) in time.
+    #[error("block {index}'s timestamp does not come after the previous block's")]
+    NonMonotonicTimestamp { index: usize },
+}
+
+/// Checks that `claims`, given oldest-first, form one unbroken chain of
+/// blocks: each claim's state picks up exactly where the previous one left
+/// off, each claim names the previous one as its parent, block numbers
+/// increase by exactly one at each step, and timestamps never go backwards.
+/// Fails fast at the first broken link and reports its index, the same way
+/// [`crate::aggregate::ComputationTree::verify`] does for sub-proof
+/// composition within a single block.
+///
+/// `allow_equal_timestamps` decides whether two consecutive blocks may
+/// share a timestamp: `true` only requires `current.timestamp >=
+/// previous.timestamp`, `false` requires it strictly greater — the way
+/// Ethereum itself requires a block's timestamp to exceed its parent's.
+pub fn verify_chain(
+    claims: &[JournalClaim],
+    allow_equal_timestamps: bool,
+) -> Result<(), ChainError> {
+    for i in 1..claims.len() {
+        let (previous, current) = (&claims[i - 1], &claims[i]);
+        if current.prev != previous.post {
+            return Err(ChainError::BrokenStateLink { index: i });
+        }
+        if current.parent_block_commitment != previous.commitment() {
+            return Err(ChainError::BrokenParentCommitment { index: i });
+        }
+        if current.block_number != previous.block_number + 1 {
+            return Err(ChainError::NonSequentialBlockNumber { index: i });
+        }
+        let in_order = if allow_equal_timestamps {
+            current.timestamp >= previous.timestamp
+        } else {
+            current.timestamp > previous.timestamp
+        };
+        if !in_order {
+            return Err(ChainError::NonMonotonicTimestamp { index: i });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::EvmAddress;
+    use crate::bundle::Bundle;
+    use crate::evm::apply_transactions;
+    use crate::tx::{Eip1559Tx, EvmTransaction, TxCommon};
+    use primitive_types::U256 as PU256;
+    use revm::db::InMemoryDB;
+    use revm::primitives::{AccountInfo, BlockEnv, SpecId, U256};
+
+    #[test]
+    fn decoding_a_committed_journal_recovers_all_three_commitments() {
+        let sender = EvmAddress::repeat_byte(0xAA);
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            crate::address::to_revm(sender),
+            AccountInfo {
+                balance: U256::from(1_000_000_000_000u64),
+                ..Default::default()
+            },
+        );
+
+        let transactions = vec![EvmTransaction::Eip1559(Eip1559Tx {
+            common: TxCommon {
+                chain_id: 1,
+                nonce: 0,
+                gas_limit: 21_000,
+                to: Some(EvmAddress::repeat_byte(0xBB)),
+                value: PU256::zero(),
+                data: vec![],
+            },
+            max_fee_per_gas: PU256::from(10u64),
+            max_priority_fee_per_gas: PU256::from(1u64),
+            access_list: vec![],
+        })];
+        let bundle = Bundle::new(sender, transactions, H256::repeat_byte(0xEE));
+
+        let prev = H256::repeat_byte(0x11);
+        let txs: Vec<_> = bundle
+            .transactions()
+            .iter()
+            .map(|tx| (sender, tx.clone()))
+            .collect();
+        let (_, log) = apply_transactions(
+            &[],
+            &[],
+            &txs,
+            BlockEnv::default(),
+            SpecId::LATEST,
+            false,
+            &mut db,
+            usize::MAX,
+        )
+        .unwrap();
+
+        let claim = JournalClaim {
+            prev,
+            post: log.commitment(),
+            bundle_commitment: bundle.commitment(),
+            block_number: 1,
+            parent_block_commitment: H256::repeat_byte(0x22),
+            timestamp: 1,
+        };
+
+        let journal = claim.encode();
+        let decoded = JournalClaim::decode(&journal).unwrap();
+
+        assert_eq!(decoded, claim);
+        assert_eq!(decoded.prev, prev);
+        assert_eq!(decoded.post, log.commitment());
+        assert_eq!(decoded.bundle_commitment, bundle.commitment());
+    }
+
+    #[test]
+    fn decode_rejects_a_journal_with_an_unsupported_version_byte() {
+        let claim = chained_claim(0x00, 0, H256::zero(), 100);
+        let mut journal = claim.encode();
+        journal[0] = crate::codec::ENCODING_VERSION + 1;
+
+        assert_eq!(
+            JournalClaim::decode(&journal),
+            Err(JournalError::UnsupportedVersion(
+                crate::codec::ENCODING_VERSION + 1
+            ))
+        );
+    }
+
+    fn chained_claim(
+        state: u8,
+        number: u64,
+        parent_block_commitment: H256,
+        timestamp: u64,
+    ) -> JournalClaim {
+        JournalClaim {
+            prev: H256::repeat_byte(state),
+            post: H256::repeat_byte(state + 1),
+            bundle_commitment: H256::repeat_byte(0xFF),
+            block_number: number,
+            parent_block_commitment,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn verify_chain_accepts_three_blocks_that_all_link_up() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_claim(0x01, 1, genesis.commitment(), 101);
+        let third = chained_claim(0x02, 2, second.commitment(), 102);
+
+        assert_eq!(verify_chain(&[genesis, second, third], false), Ok(()));
+    }
+
+    #[test]
+    fn verify_chain_rejects_a_claim_whose_prev_does_not_match_the_parents_post() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let mut second = chained_claim(0x01, 1, genesis.commitment(), 101);
+        second.prev = H256::repeat_byte(0x99);
+        let third = chained_claim(0x02, 2, second.commitment(), 102);
+
+        assert_eq!(
+            verify_chain(&[genesis, second, third], false),
+            Err(ChainError::BrokenStateLink { index: 1 })
+        );
+    }
+
+    #[test]
+    fn verify_chain_rejects_a_claim_naming_the_wrong_parent_block_commitment() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_claim(0x01, 1, genesis.commitment(), 101);
+        // Names a parent commitment that isn't `second`'s.
+        let third = chained_claim(0x02, 2, H256::repeat_byte(0xDE), 102);
+
+        assert_eq!(
+            verify_chain(&[genesis, second, third], false),
+            Err(ChainError::BrokenParentCommitment { index: 2 })
+        );
+    }
+
+    #[test]
+    fn verify_chain_rejects_a_gap_in_block_numbers() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_claim(0x01, 1, genesis.commitment(), 101);
+        let skipped = chained_claim(0x02, 3, second.commitment(), 102);
+
+        assert_eq!(
+            verify_chain(&[genesis, second, skipped], false),
+            Err(ChainError::NonSequentialBlockNumber { index: 2 })
+        );
+    }
+
+    #[test]
+    fn verify_chain_rejects_a_timestamp_that_goes_backwards() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_claim(0x01, 1, genesis.commitment(), 101);
+        let backwards = chained_claim(0x02, 2, second.commitment(), 50);
+
+        assert_eq!(
+            verify_chain(&[genesis, second, backwards], false),
+            Err(ChainError::NonMonotonicTimestamp { index: 2 })
+        );
+    }
+
+    #[test]
+    fn verify_chain_rejects_equal_timestamps_when_not_allowed() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_claim(0x01, 1, genesis.commitment(), 100);
+
+        assert_eq!(
+            verify_chain(&[genesis, second], false),
+            Err(ChainError::NonMonotonicTimestamp { index: 1 })
+        );
+    }
+
+    #[test]
+    fn verify_chain_accepts_equal_timestamps_when_allowed() {
+        let genesis = chained_claim(0x00, 0, H256::zero(), 100);
+        let second = chained_claim(0x01, 1, genesis.commitment(), 100);
+
+        assert_eq!(verify_chain(&[genesis, second], true), Ok(()));
+    }
+}
@@ -0,0 +1,40 @@
+//! amethyst: a zkEVM rollup.
+//!
+//! The guest (`log`, `bundle`) executes a bundle of transactions against a
+//! host-supplied witness and produces a commitment to the resulting state.
+//! The host (`host`, `evm`) drives real execution against live chain data to
+//! produce that witness.
+
+/// Checks a merge invariant that a well-formed input always satisfies.
+///
+/// Compiles to `assert_eq!` when the `strict` feature is enabled — kept as
+/// a hard check, even in release, for auditing a guest build — or to
+/// `debug_assert_eq!` otherwise, so a release guest skips the check and
+/// trusts its inputs instead of paying for it on every merge.
+#[macro_export]
+macro_rules! strict_assert_eq {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "strict") {
+            assert_eq!($($arg)*);
+        } else {
+            debug_assert_eq!($($arg)*);
+        }
+    };
+}
+
+pub mod address;
+pub mod aggregate;
+pub mod bundle;
+pub mod codec;
+pub mod config;
+pub mod convert;
+pub mod evm;
+pub mod genesis;
+pub mod host;
+pub mod journal;
+pub mod log;
+pub mod signature;
+pub mod trie;
+pub mod tx;
+
+pub use address::EvmAddress;
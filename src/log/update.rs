@@ -0,0 +1,245 @@
+//! A compact, versioned encoding of an [`EvmStateLog`]'s final writes, for
+//! full nodes that just want to keep their own database in sync with chain
+//! state without re-executing anything.
+//!
+//! [`encode_log`](super::encode_log)/[`decode_log`](super::decode_log)
+//! round-trip a whole [`EvmStateLog`] — every `Read` a verifier needs
+//! alongside every `Write` — which is more than a full node that already
+//! trusts a proven block needs. [`EvmStateLog::state_update_blob`] strips a
+//! log down to just its final writes: each touched account's resulting info
+//! (or its deletion), any newly-deployed code, and each touched storage
+//! slot's final value (or its clearing).
+
+use primitive_types::{H256, U256};
+use revm::primitives::{AccountInfo, Bytes};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::address::EvmAddress;
+
+use super::{Access, EvmStateLog};
+
+/// The wire format version [`EvmStateLog::state_update_blob`] encodes with.
+/// [`decode_state_update`] rejects any other version outright, rather than
+/// guessing at a shape nothing told it about.
+const STATE_UPDATE_VERSION: u8 = 1;
+
+/// An account's info as recorded in a [`StateUpdate`]: left alone, set to a
+/// new value, or deleted — distinct states, since "the log didn't touch
+/// this account's info" and "the log deleted this account" both need to
+/// survive the round trip without being confused for one another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InfoUpdate {
+    Unchanged,
+    Set(AccountInfo),
+    Deleted,
+}
+
+impl From<&Access<AccountInfo>> for InfoUpdate {
+    fn from(access: &Access<AccountInfo>) -> Self {
+        match access {
+            Access::Read(_) => InfoUpdate::Unchanged,
+            Access::Write(Some(info)) => InfoUpdate::Set(info.clone()),
+            Access::Write(None) => InfoUpdate::Deleted,
+        }
+    }
+}
+
+/// One account's final state in a [`StateUpdate`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountUpdate {
+    pub address: EvmAddress,
+    pub info: InfoUpdate,
+    /// Newly-deployed code to persist, if this account deployed any.
+    pub code: Option<Vec<u8>>,
+    /// Touched storage slots' final values, `None` where a slot was
+    /// cleared.
+    pub storage: Vec<(H256, Option<U256>)>,
+}
+
+/// The compact state delta produced by [`EvmStateLog::state_update_blob`]:
+/// every account the log actually wrote to, stripped of the reads that
+/// accompanied them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateUpdate {
+    version: u8,
+    pub accounts: Vec<AccountUpdate>,
+}
+
+impl From<&EvmStateLog> for StateUpdate {
+    fn from(log: &EvmStateLog) -> Self {
+        let accounts = log
+            .accounts
+            .iter()
+            .filter_map(|entry| {
+                let info = InfoUpdate::from(&entry.info);
+                let code = match &entry.code {
+                    Some(Access::Write(Some(code))) => Some(code.clone()),
+                    _ => None,
+                };
+                let storage: Vec<_> = entry
+                    .storage
+                    .iter()
+                    .filter_map(|(key, access)| match access {
+                        Access::Write(value) => Some((*key, *value)),
+                        Access::Read(_) => None,
+                    })
+                    .collect();
+
+                if matches!(info, InfoUpdate::Unchanged) && code.is_none() && storage.is_empty() {
+                    // Nothing written for this account; a full node has
+                    // nothing to apply.
+                    return None;
+                }
+
+                Some(AccountUpdate {
+                    address: entry.address,
+                    info,
+                    code,
+                    storage,
+                })
+            })
+            .collect();
+
+        StateUpdate {
+            version: STATE_UPDATE_VERSION,
+            accounts,
+        }
+    }
+}
+
+/// Errors raised while decoding a blob via [`decode_state_update`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum StateUpdateError {
+    /// The bytes didn't decode as a [`StateUpdate`] at all.
+    #[error("malformed state update: {0}")]
+    Malformed(String),
+    /// The bytes decoded, but under a version other than
+    /// [`STATE_UPDATE_VERSION`].
+    #[error("unsupported state update version {0}")]
+    UnsupportedVersion(u8),
+}
+
+/// Decodes a blob produced by [`EvmStateLog::state_update_blob`].
+pub fn decode_state_update(bytes: &[u8]) -> Result<StateUpdate, StateUpdateError> {
+    let update: StateUpdate =
+        bincode::deserialize(bytes).map_err(|e| StateUpdateError::Malformed(e.to_string()))?;
+    if update.version != STATE_UPDATE_VERSION {
+        return Err(StateUpdateError::UnsupportedVersion(update.version));
+    }
+    Ok(update)
+}
+
+impl EvmStateLog {
+    /// Encodes this log's final writes into a compact, versioned blob a
+    /// full node can apply to its own database to stay in sync with chain
+    /// state, without re-executing anything or needing the reads a
+    /// verifier's proofs are checked against.
+    pub fn state_update_blob(&self) -> Bytes {
+        let update = StateUpdate::from(self);
+        Bytes::from(bincode::serialize(&update).expect("StateUpdate is always serializable"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::AccountLogEntry;
+
+    fn addr(byte: u8) -> EvmAddress {
+        EvmAddress::repeat_byte(byte)
+    }
+
+    #[test]
+    fn state_update_blob_omits_an_account_that_was_only_read() {
+        let log = EvmStateLog {
+            accounts: vec![AccountLogEntry {
+                address: addr(1),
+                info: Access::Read(Some(AccountInfo::default())),
+                code: None,
+                storage: vec![],
+                storage_root: None,
+                created: false,
+            }],
+            sequencer_balances: vec![],
+        };
+
+        let update = StateUpdate::from(&log);
+
+        assert_eq!(update.accounts, vec![]);
+    }
+
+    #[test]
+    fn state_update_blob_round_trips_an_account_write_a_code_deployment_and_a_storage_clear() {
+        let info = AccountInfo {
+            balance: revm::primitives::U256::from(42),
+            nonce: 1,
+            ..Default::default()
+        };
+        let log = EvmStateLog {
+            accounts: vec![AccountLogEntry {
+                address: addr(1),
+                info: Access::Write(Some(info.clone())),
+                code: Some(Access::Write(Some(vec![0x60, 0x00]))),
+                storage: vec![
+                    (H256::repeat_byte(1), Access::Write(Some(U256::from(7)))),
+                    (H256::repeat_byte(2), Access::Write(None)),
+                ],
+                storage_root: None,
+                created: true,
+            }],
+            sequencer_balances: vec![],
+        };
+
+        let blob = log.state_update_blob();
+        let decoded = decode_state_update(&blob).unwrap();
+
+        assert_eq!(
+            decoded.accounts,
+            vec![AccountUpdate {
+                address: addr(1),
+                info: InfoUpdate::Set(info),
+                code: Some(vec![0x60, 0x00]),
+                storage: vec![
+                    (H256::repeat_byte(1), Some(U256::from(7))),
+                    (H256::repeat_byte(2), None),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn state_update_blob_reports_a_deleted_account_distinctly_from_an_untouched_one() {
+        let log = EvmStateLog {
+            accounts: vec![AccountLogEntry {
+                address: addr(1),
+                info: Access::Write(None),
+                code: None,
+                storage: vec![],
+                storage_root: None,
+                created: false,
+            }],
+            sequencer_balances: vec![],
+        };
+
+        let update = StateUpdate::from(&log);
+
+        assert_eq!(update.accounts[0].info, InfoUpdate::Deleted);
+    }
+
+    #[test]
+    fn decode_state_update_rejects_an_unsupported_version() {
+        let update = StateUpdate {
+            version: STATE_UPDATE_VERSION + 1,
+            accounts: vec![],
+        };
+        let blob = bincode::serialize(&update).unwrap();
+
+        assert_eq!(
+            decode_state_update(&blob),
+            Err(StateUpdateError::UnsupportedVersion(
+                STATE_UPDATE_VERSION + 1
+            ))
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! ECDSA signature validation for transactions.
+//!
+//! `EvmTransaction` carries no signature today — every execution entry
+//! point (e.g. [`crate::evm::run_standalone`]) takes the sender address
+//! directly, with recovery from a signature assumed to have already
+//! happened upstream. There is no `execute_transaction` yet for this to
+//! plug into. [`Signature::reject_malleable`] is the EIP-2 low-`s` check
+//! that step will need, so it exists ahead of the recovery pipeline
+//! landing rather than being invented alongside it.
+
+use primitive_types::U256 as PU256;
+use thiserror::Error;
+
+/// `n / 2`, where `n` is secp256k1's group order. Per EIP-2, a valid
+/// signature's `s` must not exceed this: the two roots `s` and `n - s` both
+/// satisfy the same signature equation, so without this bound every
+/// signature would have a second, equally valid "malleated" form.
+const SECP256K1_N_HALF: PU256 = PU256([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+/// An ECDSA signature over a transaction hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Signature {
+    pub r: PU256,
+    pub s: PU256,
+    pub v: u64,
+}
+
+/// Errors raised validating a [`Signature`].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SignatureError {
+    /// `s` is in the upper half of the curve order: the signature is the
+    /// malleated twin of a lower-`s` signature over the same transaction,
+    /// and EIP-2 requires rejecting it.
+    #[error("signature s-value {s} is malleable (must be <= n/2)")]
+    HighS { s: PU256 },
+}
+
+impl Signature {
+    /// Rejects a malleable (high-`s`) signature per EIP-2.
+    pub fn reject_malleable(&self) -> Result<(), SignatureError> {
+        if self.s > SECP256K1_N_HALF {
+            return Err(SignatureError::HighS { s: self.s });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_signature(s: PU256) -> Signature {
+        Signature {
+            r: PU256::from(0x1234u64),
+            s,
+            v: 27,
+        }
+    }
+
+    #[test]
+    fn accepts_a_valid_low_s_signature() {
+        let sig = sample_signature(PU256::from(42u64));
+        assert_eq!(sig.reject_malleable(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_the_malleated_high_s_twin_of_the_same_signature() {
+        // secp256k1's group order, `n` — only needed here to construct a
+        // high-`s` signature from a low-`s` one, via `n - s`.
+        let n = SECP256K1_N_HALF * PU256::from(2u64) + PU256::from(1u64);
+
+        let low_s = PU256::from(42u64);
+        let sig = sample_signature(low_s);
+        assert_eq!(sig.reject_malleable(), Ok(()));
+
+        // The malleated twin: same r and v, but s replaced with n - s, which
+        // is a valid signature over the same transaction and the same
+        // recovered sender.
+        let malleated = sample_signature(n - low_s);
+        assert_eq!(
+            malleated.reject_malleable(),
+            Err(SignatureError::HighS { s: malleated.s })
+        );
+    }
+
+    #[test]
+    fn accepts_s_exactly_at_the_n_half_boundary() {
+        let sig = sample_signature(SECP256K1_N_HALF);
+        assert_eq!(sig.reject_malleable(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_s_one_past_the_n_half_boundary() {
+        let sig = sample_signature(SECP256K1_N_HALF + PU256::from(1u64));
+        assert_eq!(
+            sig.reject_malleable(),
+            Err(SignatureError::HighS { s: sig.s })
+        );
+    }
+}
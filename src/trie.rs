@@ -0,0 +1,316 @@
+//! A minimal Merkle inclusion-proof scheme, standing in for the
+//! Merkle-Patricia trie this crate doesn't implement yet (see
+//! [`crate::genesis`]). It supports exactly what [`crate::log::apply_rw_log`]
+//! needs — committing to a fixed list of leaves and proving individual
+//! leaves' membership — without an MPT's update/delete/range machinery.
+
+use primitive_types::H256;
+use sha3::{Digest, Keccak256};
+
+/// Ethereum's well-known empty-trie root, `keccak256(rlp(""))` —
+/// `keccak256(0x80)`. The root of any real Merkle-Patricia trie with no
+/// entries is this value, not some encoding-specific placeholder, so it's
+/// what [`crate::genesis::apply_genesis`] returns for a genesis with no
+/// allocation.
+pub const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+fn hash_pair(left: H256, right: H256) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    H256::from(hasher.finalize().as_ref())
+}
+
+/// A binary Merkle tree built bottom-up over a fixed list of leaves. Odd
+/// levels pad by duplicating the last node, so any leaf count works.
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    levels: Vec<Vec<H256>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree over `leaves`. Panics if `leaves` is empty, since an
+    /// empty tree has no root to commit to.
+    pub fn build(leaves: &[H256]) -> MerkleTree {
+        assert!(
+            !leaves.is_empty(),
+            "MerkleTree::build needs at least one leaf"
+        );
+        let mut levels = vec![leaves.to_vec()];
+        while levels.last().expect("levels is never empty").len() > 1 {
+            let prev = levels.last().expect("levels is never empty");
+            let next = prev
+                .chunks(2)
+                .map(|pair| {
+                    let left = pair[0];
+                    let right = *pair.get(1).unwrap_or(&pair[0]);
+                    hash_pair(left, right)
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    /// Commits to `leaves` directly, for callers that only need the root.
+    pub fn commit(leaves: &[H256]) -> H256 {
+        Self::build(leaves).root()
+    }
+
+    /// The tree's root, i.e. its commitment to every leaf.
+    pub fn root(&self) -> H256 {
+        self.levels.last().expect("levels is never empty")[0]
+    }
+
+    /// Builds the inclusion proof for the leaf at `leaf_index`.
+    pub fn prove(&self, leaf_index: usize) -> MerkleProof {
+        let mut siblings = Vec::with_capacity(self.levels.len() - 1);
+        let mut index = leaf_index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling_index = if index.is_multiple_of(2) {
+                index + 1
+            } else {
+                index - 1
+            };
+            siblings.push(*level.get(sibling_index).unwrap_or(&level[index]));
+            index /= 2;
+        }
+        MerkleProof {
+            leaf_index,
+            siblings,
+        }
+    }
+
+    /// This tree's depth: how many levels separate a leaf from the root.
+    pub fn height(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Host builder for a [`ProofBundle`]: extracts exactly the nodes on
+    /// the paths from the leaves at `leaf_indices` up to the root, so the
+    /// guest can verify reads of just those leaves without receiving the
+    /// rest of the tree.
+    pub fn build_proof_bundle(&self, leaf_indices: &[usize]) -> ProofBundle {
+        let mut nodes = std::collections::HashMap::new();
+        for &leaf_index in leaf_indices {
+            let mut index = leaf_index;
+            for level in &self.levels[..self.levels.len() - 1] {
+                let pair_index = index - index % 2;
+                let left = level[pair_index];
+                let right = *level.get(pair_index + 1).unwrap_or(&left);
+                let parent = hash_pair(left, right);
+                let mut bytes = [0u8; 64];
+                bytes[..32].copy_from_slice(left.as_bytes());
+                bytes[32..].copy_from_slice(right.as_bytes());
+                nodes.insert(parent, bytes);
+                index /= 2;
+            }
+        }
+        ProofBundle { nodes }
+    }
+}
+
+/// A minimal "proof bundle": exactly the trie nodes needed to verify reads
+/// of a chosen subset of a [`MerkleTree`]'s leaves, without shipping the
+/// whole tree — built by [`MerkleTree::build_proof_bundle`].
+///
+/// Each node is keyed by its own hash, with its value holding the 64 raw
+/// bytes ([`hash_pair`]'s preimage) of its two children's hashes — the
+/// role a real trie node's RLP-encoded children would play, just without
+/// RLP (see the module doc). Nodes shared by more than one requested
+/// leaf's path — a common ancestor — are only stored once, so a bundle
+/// for several leaves is usually much smaller than that many independent
+/// [`MerkleProof`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProofBundle {
+    nodes: std::collections::HashMap<H256, [u8; 64]>,
+}
+
+impl ProofBundle {
+    /// Looks up a node's raw bytes by its hash, the same way a real trie's
+    /// node store would.
+    pub fn get(&self, hash: H256) -> Option<&[u8]> {
+        self.nodes.get(&hash).map(|bytes| bytes.as_slice())
+    }
+
+    /// Verifies that `leaf` is the leaf a [`MerkleTree`] of depth `height`
+    /// (see [`MerkleTree::height`]) committed to at `leaf_index` under
+    /// `root`, walking down from `root` through this bundle's nodes
+    /// instead of up through a single leaf's own sibling list like
+    /// [`MerkleProof::verify`] does.
+    pub fn verify_leaf(&self, root: H256, height: usize, leaf_index: usize, leaf: H256) -> bool {
+        let mut hash = root;
+        for level in (0..height).rev() {
+            let bytes = match self.get(hash) {
+                Some(bytes) => bytes,
+                None => return false,
+            };
+            let left = H256::from_slice(&bytes[..32]);
+            let right = H256::from_slice(&bytes[32..]);
+            hash = if (leaf_index >> level) & 1 == 0 {
+                left
+            } else {
+                right
+            };
+        }
+        hash == leaf
+    }
+}
+
+/// A proof that a single leaf is included in the tree committed to by some
+/// root, independent of the rest of the tree's contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<H256>,
+}
+
+/// The leaf a position holds when nothing has been recorded there, for
+/// proving absence the same way any other leaf proves presence: build the
+/// tree with this value at that index, and a proof against it is an
+/// exclusion proof rather than an inclusion proof.
+///
+/// This crate has no sparse, key-addressed trie yet (see the module doc),
+/// so exclusion can't be proven by position alone the way a real MPT would
+/// — it only works for a tree the prover deliberately built with this
+/// sentinel at the excluded index.
+pub const EXCLUSION_LEAF: H256 = H256::zero();
+
+impl MerkleProof {
+    /// Checks that `leaf` is included under `root` at this proof's index.
+    pub fn verify(&self, root: H256, leaf: H256) -> bool {
+        let mut hash = leaf;
+        let mut index = self.leaf_index;
+        for sibling in &self.siblings {
+            hash = if index.is_multiple_of(2) {
+                hash_pair(hash, *sibling)
+            } else {
+                hash_pair(*sibling, hash)
+            };
+            index /= 2;
+        }
+        hash == root
+    }
+
+    /// Verifies this proof against `root` for a claimed leaf: `Some(leaf)`
+    /// checks an ordinary inclusion proof of `leaf`; `None` checks an
+    /// exclusion proof, i.e. that the tree holds [`EXCLUSION_LEAF`] at this
+    /// index instead of any real leaf. One entry point for both, so a
+    /// caller verifying a `Read` access's claimed value (`Some` for a
+    /// genuine read, `None` for a read that found nothing) doesn't need to
+    /// branch on which kind of proof it's holding.
+    pub fn verify_proof(&self, root: H256, claimed: Option<H256>) -> bool {
+        self.verify(root, claimed.unwrap_or(EXCLUSION_LEAF))
+    }
+
+    /// Checks that this proof attests to the absence of any real leaf at
+    /// its index: the tree must commit to [`EXCLUSION_LEAF`] there instead
+    /// of to a real leaf's hash.
+    pub fn verify_exclusion(&self, root: H256) -> bool {
+        self.verify_proof(root, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(byte: u8) -> H256 {
+        H256::repeat_byte(byte)
+    }
+
+    #[test]
+    fn proof_verifies_every_leaf_in_an_odd_sized_tree() {
+        let leaves = vec![leaf(1), leaf(2), leaf(3)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.prove(index);
+            assert!(proof.verify(root, *leaf));
+        }
+    }
+
+    #[test]
+    fn proof_rejects_a_forged_leaf() {
+        let leaves = vec![leaf(1), leaf(2), leaf(3), leaf(4)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+
+        let proof = tree.prove(1);
+        assert!(!proof.verify(root, leaf(0xff)));
+    }
+
+    #[test]
+    fn verify_exclusion_accepts_a_position_holding_the_exclusion_leaf() {
+        let leaves = vec![leaf(1), EXCLUSION_LEAF, leaf(3)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+
+        let proof = tree.prove(1);
+        assert!(proof.verify_exclusion(root));
+    }
+
+    #[test]
+    fn verify_exclusion_rejects_a_position_holding_a_real_leaf() {
+        let leaves = vec![leaf(1), leaf(2), leaf(3)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+
+        let proof = tree.prove(1);
+        assert!(!proof.verify_exclusion(root));
+    }
+
+    #[test]
+    fn proof_bundle_verifies_reads_for_exactly_the_requested_leaves() {
+        let leaves = vec![leaf(1), leaf(2), leaf(3), leaf(4), leaf(5)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+        let height = tree.height();
+
+        let bundle = tree.build_proof_bundle(&[0, 3]);
+
+        assert!(bundle.verify_leaf(root, height, 0, leaves[0]));
+        assert!(bundle.verify_leaf(root, height, 3, leaves[3]));
+    }
+
+    #[test]
+    fn proof_bundle_rejects_a_leaf_whose_path_was_not_built_for() {
+        let leaves = vec![leaf(1), leaf(2), leaf(3), leaf(4), leaf(5)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+        let height = tree.height();
+
+        // Leaf 4's path shares no node with leaves 0 or 3's paths (beyond
+        // the root), so it isn't verifiable from a bundle built for those.
+        let bundle = tree.build_proof_bundle(&[0, 3]);
+
+        assert!(!bundle.verify_leaf(root, height, 4, leaves[4]));
+    }
+
+    #[test]
+    fn proof_bundle_rejects_a_forged_leaf_value() {
+        let leaves = vec![leaf(1), leaf(2), leaf(3), leaf(4), leaf(5)];
+        let tree = MerkleTree::build(&leaves);
+        let root = tree.root();
+        let height = tree.height();
+
+        let bundle = tree.build_proof_bundle(&[0]);
+
+        assert!(!bundle.verify_leaf(root, height, 0, leaf(0xff)));
+    }
+
+    #[test]
+    fn empty_root_matches_ethereums_known_keccak256_of_rlp_empty_string() {
+        // RLP encodes the empty byte string as the single byte 0x80 —
+        // independent of this crate's own (de)serialization choices, so
+        // this is computed from that raw byte rather than via any of this
+        // crate's own encoding helpers.
+        let rlp_empty_string = [0x80u8];
+        let expected = H256::from(Keccak256::digest(rlp_empty_string).as_ref());
+        assert_eq!(EMPTY_ROOT, expected);
+    }
+}
@@ -0,0 +1,413 @@
+//! EVM transaction types and their translation into a revm [`TxEnv`].
+
+use primitive_types::{H256, U256 as PU256};
+use revm::primitives::{Address, Bytes, TransactTo, TxEnv, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::address::{self, EvmAddress};
+use crate::bundle::DeserializationError;
+
+/// Fields shared by every transaction type.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TxCommon {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub to: Option<EvmAddress>,
+    pub value: PU256,
+    pub data: Vec<u8>,
+}
+
+/// A pre-EIP-1559 transaction, or an EIP-2930 transaction carrying an access
+/// list. Both pay a single flat `gas_price` and have no notion of a
+/// priority fee: the effective gas price is `gas_price`, independent of the
+/// block's base fee.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LegacyTx {
+    pub common: TxCommon,
+    pub gas_price: PU256,
+    /// Non-empty only for EIP-2930 transactions; empty for plain legacy.
+    pub access_list: Vec<(EvmAddress, Vec<PU256>)>,
+}
+
+/// An EIP-1559 transaction. The effective gas price is
+/// `min(max_fee_per_gas, block.basefee + max_priority_fee_per_gas)`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Eip1559Tx {
+    pub common: TxCommon,
+    pub max_fee_per_gas: PU256,
+    pub max_priority_fee_per_gas: PU256,
+    pub access_list: Vec<(EvmAddress, Vec<PU256>)>,
+}
+
+/// An EIP-7702 set-code authorization: an EOA (`authority`) delegating its
+/// code to `address` for the lifetime of the transaction carrying it,
+/// scoped to `chain_id` and `authority`'s next nonce.
+///
+/// The real EIP-7702 tuple is `(chain_id, address, nonce, y_parity, r, s)`,
+/// with a verifier recovering `authority` from the signature. This crate has
+/// no ECDSA signature-recovery pipeline (see [`crate::signature`]) and no
+/// secp256k1 dependency to build one from, so `authority` is carried
+/// directly — the same way [`EvmTransaction`] itself carries no signature
+/// and takes its sender address directly — and `mac` stands in for
+/// `(y_parity, r, s)`: [`sign_authorization`]'s symmetric Keccak256 MAC,
+/// keyed on `authority` itself rather than a private key this crate has no
+/// way to recover an address from. It binds the authorization to the
+/// address it claims to be from, the same way [`crate::bundle::sign_bundle`]
+/// binds a bundle to its claimed sequencer, though being keyed on public
+/// data it isn't the actual cryptographic guarantee a real signature gives.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SetCodeAuthorization {
+    pub chain_id: u64,
+    pub address: EvmAddress,
+    pub nonce: u64,
+    pub authority: EvmAddress,
+    pub mac: H256,
+}
+
+/// Computes the MAC [`SetCodeAuthorization::mac`] must carry for an
+/// authorization with these fields to be accepted.
+pub fn sign_authorization(
+    chain_id: u64,
+    address: EvmAddress,
+    nonce: u64,
+    authority: EvmAddress,
+) -> H256 {
+    let mut preimage = authority.as_bytes().to_vec();
+    preimage.extend_from_slice(&chain_id.to_be_bytes());
+    preimage.extend_from_slice(address.as_bytes());
+    preimage.extend_from_slice(&nonce.to_be_bytes());
+    H256::from(Keccak256::digest(&preimage).as_ref())
+}
+
+impl SetCodeAuthorization {
+    /// Checks this authorization's `mac` against [`sign_authorization`]'s
+    /// recomputation — the role recovering `authority` from a real ECDSA
+    /// signature would play. A mismatch here is what
+    /// [`crate::evm::apply_set_code_authorizations`] skips rather than
+    /// failing the whole transaction over, per EIP-7702.
+    pub fn verify(&self) -> bool {
+        sign_authorization(self.chain_id, self.address, self.nonce, self.authority) == self.mac
+    }
+}
+
+/// An EIP-7702 set-code transaction: an EIP-1559 fee market transaction that
+/// also carries an `authorization_list`, each entry letting an EOA delegate
+/// its code to a contract for this transaction's execution.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SetCodeTx {
+    pub common: TxCommon,
+    pub max_fee_per_gas: PU256,
+    pub max_priority_fee_per_gas: PU256,
+    pub access_list: Vec<(EvmAddress, Vec<PU256>)>,
+    pub authorization_list: Vec<SetCodeAuthorization>,
+}
+
+/// An EVM transaction, in one of the forms amethyst accepts.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum EvmTransaction {
+    Legacy(LegacyTx),
+    Eip1559(Eip1559Tx),
+    SetCode(SetCodeTx),
+}
+
+fn to_transact_to(to: Option<EvmAddress>) -> TransactTo {
+    match to {
+        Some(addr) => TransactTo::Call(address::to_revm(addr)),
+        None => TransactTo::create(),
+    }
+}
+
+fn to_access_list(list: &[(EvmAddress, Vec<PU256>)]) -> Vec<(Address, Vec<U256>)> {
+    list.iter()
+        .map(|(addr, keys)| {
+            (
+                address::to_revm(*addr),
+                keys.iter().map(|k| U256::from_limbs(k.0)).collect(),
+            )
+        })
+        .collect()
+}
+
+impl EvmTransaction {
+    /// This transaction's self-reported gas limit.
+    pub fn gas_limit(&self) -> u64 {
+        match self {
+            EvmTransaction::Legacy(tx) => tx.common.gas_limit,
+            EvmTransaction::Eip1559(tx) => tx.common.gas_limit,
+            EvmTransaction::SetCode(tx) => tx.common.gas_limit,
+        }
+    }
+
+    /// This transaction's self-reported nonce.
+    pub fn nonce(&self) -> u64 {
+        match self {
+            EvmTransaction::Legacy(tx) => tx.common.nonce,
+            EvmTransaction::Eip1559(tx) => tx.common.nonce,
+            EvmTransaction::SetCode(tx) => tx.common.nonce,
+        }
+    }
+
+    /// This transaction's access list. Every variant carries one, even
+    /// [`EvmTransaction::Legacy`] — see [`LegacyTx::access_list`]'s own doc
+    /// comment for why a plain legacy transaction's is always empty.
+    pub fn access_list(&self) -> &[(EvmAddress, Vec<PU256>)] {
+        match self {
+            EvmTransaction::Legacy(tx) => &tx.access_list,
+            EvmTransaction::Eip1559(tx) => &tx.access_list,
+            EvmTransaction::SetCode(tx) => &tx.access_list,
+        }
+    }
+
+    /// This transaction's authorization list, if it has one — only
+    /// [`EvmTransaction::SetCode`] does; every other variant reports empty.
+    ///
+    /// revm 3.3's `TxEnv` has no `authorization_list` field, so
+    /// [`EvmTransaction::add_to_env`] can't hand these to revm itself — a
+    /// caller wanting to apply them calls
+    /// [`crate::evm::apply_set_code_authorizations`] against this list
+    /// directly, ahead of running the transaction.
+    pub fn authorization_list(&self) -> &[SetCodeAuthorization] {
+        match self {
+            EvmTransaction::Legacy(_) | EvmTransaction::Eip1559(_) => &[],
+            EvmTransaction::SetCode(tx) => &tx.authorization_list,
+        }
+    }
+
+    /// Populates `env` with this transaction's fields.
+    ///
+    /// The three arms set `gas_priority_fee` differently: legacy and
+    /// EIP-2930 transactions have no priority fee concept, so it is left
+    /// `None` and revm's [`Env::effective_gas_price`] falls back to
+    /// `gas_price` regardless of the block's base fee. EIP-1559 and
+    /// [`EvmTransaction::SetCode`] transactions set it to
+    /// `Some(max_priority_fee_per_gas)`, so the effective price is computed
+    /// against the base fee. `SetCode`'s `authorization_list` isn't set
+    /// here — see [`EvmTransaction::authorization_list`].
+    pub fn add_to_env(&self, caller: EvmAddress, env: &mut TxEnv) {
+        env.caller = address::to_revm(caller);
+        match self {
+            EvmTransaction::Legacy(tx) => {
+                env.nonce = Some(tx.common.nonce);
+                env.chain_id = Some(tx.common.chain_id);
+                env.gas_limit = tx.common.gas_limit;
+                env.gas_price = U256::from_limbs(tx.gas_price.0);
+                env.gas_priority_fee = None;
+                env.transact_to = to_transact_to(tx.common.to);
+                env.value = U256::from_limbs(tx.common.value.0);
+                env.data = Bytes::from(tx.common.data.clone());
+                env.access_list = to_access_list(&tx.access_list);
+            }
+            EvmTransaction::Eip1559(tx) => {
+                env.nonce = Some(tx.common.nonce);
+                env.chain_id = Some(tx.common.chain_id);
+                env.gas_limit = tx.common.gas_limit;
+                env.gas_price = U256::from_limbs(tx.max_fee_per_gas.0);
+                env.gas_priority_fee = Some(U256::from_limbs(tx.max_priority_fee_per_gas.0));
+                env.transact_to = to_transact_to(tx.common.to);
+                env.value = U256::from_limbs(tx.common.value.0);
+                env.data = Bytes::from(tx.common.data.clone());
+                env.access_list = to_access_list(&tx.access_list);
+            }
+            EvmTransaction::SetCode(tx) => {
+                env.nonce = Some(tx.common.nonce);
+                env.chain_id = Some(tx.common.chain_id);
+                env.gas_limit = tx.common.gas_limit;
+                env.gas_price = U256::from_limbs(tx.max_fee_per_gas.0);
+                env.gas_priority_fee = Some(U256::from_limbs(tx.max_priority_fee_per_gas.0));
+                env.transact_to = to_transact_to(tx.common.to);
+                env.value = U256::from_limbs(tx.common.value.0);
+                env.data = Bytes::from(tx.common.data.clone());
+                env.access_list = to_access_list(&tx.access_list);
+            }
+        }
+    }
+}
+
+/// Decodes an [`EvmTransaction`] from the bytes produced by serializing one
+/// with `bincode` — the same wire format [`crate::bundle::serialize_bundle`]
+/// uses for the transactions inside a bundle.
+///
+/// There is no EIP-2718 typed-transaction decoder in this crate (no
+/// `decode_typed_tx`, and no separate `TransactionBody` type to decode one
+/// into): the serde-derived `EvmTransaction` enum is already
+/// self-describing, so there's nothing left over to decode as a "body"
+/// once the transaction itself is decoded.
+impl TryFrom<&[u8]> for EvmTransaction {
+    type Error = DeserializationError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        bincode::deserialize(bytes).map_err(|e| DeserializationError::Malformed(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::Env;
+
+    fn common() -> TxCommon {
+        TxCommon {
+            chain_id: 1,
+            nonce: 0,
+            gas_limit: 21_000,
+            to: Some(EvmAddress::repeat_byte(0xAA)),
+            value: PU256::zero(),
+            data: vec![],
+        }
+    }
+
+    #[test]
+    fn legacy_effective_gas_price_ignores_basefee() {
+        let tx = EvmTransaction::Legacy(LegacyTx {
+            common: common(),
+            gas_price: PU256::from(100u64),
+            access_list: vec![],
+        });
+
+        let mut env = Env::default();
+        env.block.basefee = U256::from(40);
+        tx.add_to_env(EvmAddress::repeat_byte(1), &mut env.tx);
+
+        assert_eq!(env.tx.gas_priority_fee, None);
+        assert_eq!(env.effective_gas_price(), U256::from(100));
+    }
+
+    #[test]
+    fn eip1559_effective_gas_price_is_capped_by_basefee_plus_priority() {
+        let tx = EvmTransaction::Eip1559(Eip1559Tx {
+            common: common(),
+            max_fee_per_gas: PU256::from(100u64),
+            max_priority_fee_per_gas: PU256::from(10u64),
+            access_list: vec![],
+        });
+
+        let mut env = Env::default();
+        env.block.basefee = U256::from(40);
+        tx.add_to_env(EvmAddress::repeat_byte(1), &mut env.tx);
+
+        assert_eq!(env.tx.gas_priority_fee, Some(U256::from(10)));
+        // min(max_fee_per_gas, basefee + priority_fee) = min(100, 50) = 50
+        assert_eq!(env.effective_gas_price(), U256::from(50));
+    }
+
+    #[test]
+    fn legacy_and_eip1559_diverge_under_the_same_basefee() {
+        let basefee = U256::from(40);
+
+        let legacy = EvmTransaction::Legacy(LegacyTx {
+            common: common(),
+            gas_price: PU256::from(100u64),
+            access_list: vec![],
+        });
+        let eip1559 = EvmTransaction::Eip1559(Eip1559Tx {
+            common: common(),
+            max_fee_per_gas: PU256::from(100u64),
+            max_priority_fee_per_gas: PU256::from(10u64),
+            access_list: vec![],
+        });
+
+        let mut legacy_env = Env::default();
+        legacy_env.block.basefee = basefee;
+        legacy.add_to_env(EvmAddress::repeat_byte(1), &mut legacy_env.tx);
+
+        let mut eip1559_env = Env::default();
+        eip1559_env.block.basefee = basefee;
+        eip1559.add_to_env(EvmAddress::repeat_byte(1), &mut eip1559_env.tx);
+
+        assert_ne!(
+            legacy_env.effective_gas_price(),
+            eip1559_env.effective_gas_price()
+        );
+    }
+
+    #[test]
+    fn legacy_transaction_round_trips_through_try_from_bytes() {
+        let tx = EvmTransaction::Legacy(LegacyTx {
+            common: common(),
+            gas_price: PU256::from(100u64),
+            access_list: vec![],
+        });
+
+        let bytes = bincode::serialize(&tx).unwrap();
+        let decoded = EvmTransaction::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(decoded, tx);
+    }
+
+    #[test]
+    fn eip1559_transaction_round_trips_through_try_from_bytes() {
+        let tx = EvmTransaction::Eip1559(Eip1559Tx {
+            common: common(),
+            max_fee_per_gas: PU256::from(100u64),
+            max_priority_fee_per_gas: PU256::from(10u64),
+            access_list: vec![],
+        });
+
+        let bytes = bincode::serialize(&tx).unwrap();
+        let decoded = EvmTransaction::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(decoded, tx);
+    }
+
+    #[test]
+    fn try_from_rejects_malformed_bytes() {
+        let result = EvmTransaction::try_from(&[0xFFu8; 4][..]);
+
+        assert!(matches!(result, Err(DeserializationError::Malformed(_))));
+    }
+
+    fn authorization(authority: EvmAddress) -> SetCodeAuthorization {
+        let chain_id = 1;
+        let delegate = EvmAddress::repeat_byte(0xDD);
+        let nonce = 0;
+        SetCodeAuthorization {
+            chain_id,
+            address: delegate,
+            nonce,
+            authority,
+            mac: sign_authorization(chain_id, delegate, nonce, authority),
+        }
+    }
+
+    #[test]
+    fn a_correctly_signed_authorization_verifies() {
+        let auth = authorization(EvmAddress::repeat_byte(0xEE));
+        assert!(auth.verify());
+    }
+
+    #[test]
+    fn an_authorization_claiming_the_wrong_authority_does_not_verify() {
+        let mut auth = authorization(EvmAddress::repeat_byte(0xEE));
+        auth.authority = EvmAddress::repeat_byte(0xFF);
+        assert!(!auth.verify());
+    }
+
+    #[test]
+    fn set_code_transaction_round_trips_through_try_from_bytes() {
+        let tx = EvmTransaction::SetCode(SetCodeTx {
+            common: common(),
+            max_fee_per_gas: PU256::from(100u64),
+            max_priority_fee_per_gas: PU256::from(10u64),
+            access_list: vec![],
+            authorization_list: vec![authorization(EvmAddress::repeat_byte(0xEE))],
+        });
+
+        let bytes = bincode::serialize(&tx).unwrap();
+        let decoded = EvmTransaction::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(decoded, tx);
+    }
+
+    #[test]
+    fn authorization_list_is_empty_for_non_set_code_transactions() {
+        let tx = EvmTransaction::Eip1559(Eip1559Tx {
+            common: common(),
+            max_fee_per_gas: PU256::from(100u64),
+            max_priority_fee_per_gas: PU256::from(10u64),
+            access_list: vec![],
+        });
+
+        assert!(tx.authorization_list().is_empty());
+    }
+}